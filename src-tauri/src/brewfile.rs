@@ -0,0 +1,946 @@
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fmt::Write as _;
+use std::process::Command;
+use tauri::Manager;
+
+use crate::history;
+use crate::homebrew::{
+    execute_brew_command, execute_brew_command_queued, package_args, CommandOutput,
+};
+use crate::operations::{self, QueueGuard};
+use crate::services::list_services_internal;
+use crate::snapshots::auto_snapshot;
+
+/// Brewfile 导出选项
+#[derive(Debug, Default, Serialize, Deserialize)]
+#[serde(default, rename_all = "camelCase")]
+pub struct ExportBrewfileOptions {
+    /// 写入的文件路径；为空时只返回文本
+    pub path: Option<String>,
+    /// 只导出主动安装且没有被其他包依赖的 formula（brew leaves）
+    pub leaves_only: bool,
+    /// 在每个条目前加上描述注释
+    pub include_descriptions: bool,
+}
+
+#[derive(Debug, Deserialize)]
+pub(crate) struct InstalledStateJson {
+    pub formulae: Vec<InstalledFormulaJson>,
+    pub casks: Vec<InstalledCaskJson>,
+}
+
+#[derive(Debug, Deserialize)]
+pub(crate) struct InstalledFormulaJson {
+    pub name: String,
+    pub full_name: String,
+    pub tap: Option<String>,
+    pub desc: Option<String>,
+    #[serde(default)]
+    pub keg_only: bool,
+    pub keg_only_reason: Option<KegOnlyReasonJson>,
+    pub caveats: Option<String>,
+    pub linked_keg: Option<String>,
+    #[serde(default)]
+    pub outdated: bool,
+    #[serde(default)]
+    pub pinned: bool,
+    #[serde(default)]
+    pub installed: Vec<InstalledVersionJson>,
+    #[serde(default)]
+    pub dependencies: Vec<String>,
+}
+
+#[derive(Debug, Deserialize)]
+pub(crate) struct KegOnlyReasonJson {
+    pub reason: String,
+    #[serde(default)]
+    pub explanation: String,
+}
+
+#[derive(Debug, Deserialize)]
+pub(crate) struct InstalledVersionJson {
+    pub version: String,
+    #[serde(default)]
+    pub installed_on_request: bool,
+}
+
+#[derive(Debug, Deserialize)]
+pub(crate) struct InstalledCaskJson {
+    pub token: String,
+    pub full_token: String,
+    pub tap: Option<String>,
+    pub desc: Option<String>,
+    #[serde(default)]
+    pub outdated: bool,
+    pub installed: Option<String>,
+    #[serde(default)]
+    pub artifacts: Vec<serde_json::Value>,
+    #[serde(default)]
+    pub depends_on: serde_json::Value,
+}
+
+/// 获取已安装状态（brew info --installed --json=v2）
+pub(crate) fn installed_state() -> Result<InstalledStateJson, String> {
+    let output = execute_brew_command(&["info", "--installed", "--json=v2"])?;
+    
+    if !output.success {
+        return Err(format!(
+            "Failed to list installed packages: {}",
+            output.stderr
+        ));
+    }
+    
+    serde_json::from_str(&output.stdout)
+        .map_err(|e| format!("Failed to parse installed JSON: {}", e))
+}
+
+/// 已添加的 tap 名称（brew tap）
+pub(crate) fn installed_taps() -> Result<Vec<String>, String> {
+    let output = execute_brew_command(&["tap"])?;
+    
+    if !output.success {
+        return Err(format!("Failed to list taps: {}", output.stderr));
+    }
+    
+    Ok(output
+        .stdout
+        .lines()
+        .map(|line| line.trim().to_string())
+        .filter(|line| !line.is_empty())
+        .collect())
+}
+
+/// 没有被其他包依赖的 formula（brew leaves）
+pub(crate) fn installed_leaves(on_request_only: bool) -> Result<Vec<String>, String> {
+    let mut args = vec!["leaves"];
+    if on_request_only {
+        args.push("--installed-on-request");
+    }
+    let output = execute_brew_command(&args)?;
+    
+    if !output.success {
+        return Err(format!("Failed to list leaves: {}", output.stderr));
+    }
+    
+    Ok(output
+        .stdout
+        .lines()
+        .map(|line| line.trim().to_string())
+        .filter(|line| !line.is_empty())
+        .collect())
+}
+
+fn push_description(out: &mut String, desc: Option<&str>, enabled: bool) {
+    if let Some(desc) = desc.filter(|d| enabled && !d.is_empty()) {
+        let _ = writeln!(out, "# {}", desc);
+    }
+}
+
+/// 根据当前安装状态生成 Brewfile
+fn generate_brewfile(options: &ExportBrewfileOptions) -> Result<String, String> {
+    let state = installed_state()?;
+    let taps = installed_taps()?;
+    let running: Vec<String> = list_services_internal()
+        .unwrap_or_default()
+        .into_iter()
+        .filter(|s| s.status == "started")
+        .map(|s| s.name)
+        .collect();
+    let leaves = if options.leaves_only {
+        Some(installed_leaves(true)?)
+    } else {
+        None
+    };
+    
+    let mut out = String::new();
+    
+    for tap in &taps {
+        let _ = writeln!(out, "tap \"{}\"", tap);
+    }
+    
+    for formula in &state.formulae {
+        if let Some(leaves) = &leaves {
+            if !leaves.contains(&formula.name) && !leaves.contains(&formula.full_name) {
+                continue;
+            }
+        }
+        push_description(
+            &mut out,
+            formula.desc.as_deref(),
+            options.include_descriptions,
+        );
+        
+        let mut line = format!("brew \"{}\"", formula.full_name);
+        if running.contains(&formula.name) {
+            line.push_str(", restart_service: :changed");
+        }
+        let linked = formula.linked_keg.is_some();
+        if formula.keg_only && linked {
+            line.push_str(", link: true");
+        } else if !formula.keg_only && !linked {
+            line.push_str(", link: false");
+        }
+        let _ = writeln!(out, "{}", line);
+    }
+    
+    for cask in &state.casks {
+        push_description(&mut out, cask.desc.as_deref(), options.include_descriptions);
+        let _ = writeln!(out, "cask \"{}\"", cask.full_token);
+    }
+    
+    Ok(out)
+}
+
+/// 导出 Brewfile；指定路径时同时写入文件
+#[tauri::command]
+pub async fn export_brewfile(options: Option<ExportBrewfileOptions>) -> Result<String, String> {
+    let options = options.unwrap_or_default();
+    let content = generate_brewfile(&options)?;
+    
+    if let Some(path) = options.path.as_deref().filter(|p| !p.trim().is_empty()) {
+        std::fs::write(path, &content).map_err(|e| format!("Failed to write Brewfile: {}", e))?;
+    }
+    
+    Ok(content)
+}
+
+/// Brewfile 中的一个条目
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BrewfileEntry {
+    /// tap / brew / cask / mas / vscode / whalebrew
+    pub kind: String,
+    pub name: String,
+    /// 条目选项的原始值，如 `restart_service` -> `:changed`
+    pub options: BTreeMap<String, String>,
+    /// 所在行号（从 1 开始）
+    pub line: usize,
+}
+
+impl BrewfileEntry {
+    /// 不含 tap 前缀的包名，`hashicorp/tap/terraform` -> `terraform`
+    fn short_name(&self) -> &str {
+        self.name.rsplit('/').next().unwrap_or(&self.name)
+    }
+    
+    fn option(&self, key: &str) -> Option<&str> {
+        self.options.get(key).map(|v| v.as_str())
+    }
+}
+
+/// 已安装但 Brewfile 中没有列出的包
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ExtraPackage {
+    pub name: String,
+    #[serde(rename = "type")]
+    pub pkg_type: String,
+}
+
+/// Brewfile 与当前安装状态的差异
+#[derive(Debug, Serialize, Deserialize)]
+pub struct BrewfilePlan {
+    pub path: String,
+    pub entries: Vec<BrewfileEntry>,
+    pub to_install: Vec<BrewfileEntry>,
+    pub to_upgrade: Vec<BrewfileEntry>,
+    /// 已安装但未列出的包（formula 仅统计 leaves）
+    pub extra_installed: Vec<ExtraPackage>,
+    /// 无法处理的条目（如未安装 mas 时的 App Store 应用）
+    pub skipped: Vec<BrewfileEntry>,
+}
+
+/// Brewfile 应用选项
+#[derive(Debug, Default, Serialize, Deserialize)]
+#[serde(default, rename_all = "camelCase")]
+pub struct ApplyBrewfileOptions {
+    /// 不升级已安装但过时的包
+    pub no_upgrade: bool,
+    /// 卸载 Brewfile 中没有列出的包
+    pub cleanup: bool,
+}
+
+/// 单个条目的执行结果
+#[derive(Debug, Serialize, Deserialize)]
+pub struct BrewfileEntryResult {
+    pub kind: String,
+    pub name: String,
+    /// tap / install / upgrade / uninstall
+    pub action: String,
+    pub success: bool,
+    pub message: String,
+}
+
+/// 按顶层逗号拆分，忽略引号和括号内的逗号
+fn split_top_level(s: &str) -> Vec<String> {
+    let mut parts = Vec::new();
+    let mut current = String::new();
+    let mut depth = 0i32;
+    let mut quote: Option<char> = None;
+    
+    for c in s.chars() {
+        match (quote, c) {
+            (Some(q), c) if c == q => quote = None,
+            (Some(_), _) => {}
+            (None, '"') | (None, '\'') => quote = Some(c),
+            (None, '[') | (None, '{') | (None, '(') => depth += 1,
+            (None, ']') | (None, '}') | (None, ')') => depth -= 1,
+            (None, ',') if depth == 0 => {
+                parts.push(current.trim().to_string());
+                current.clear();
+                continue;
+            }
+            _ => {}
+        }
+        current.push(c);
+    }
+    if !current.trim().is_empty() {
+        parts.push(current.trim().to_string());
+    }
+    
+    parts
+}
+
+/// 去掉字符串字面量两侧的引号
+fn unquote(s: &str) -> String {
+    let s = s.trim();
+    for q in ['"', '\''] {
+        if s.len() >= 2 && s.starts_with(q) && s.ends_with(q) {
+            return s[1..s.len() - 1].to_string();
+        }
+    }
+    s.to_string()
+}
+
+/// 去掉行尾注释（引号内的 # 保留）
+fn strip_comment(line: &str) -> &str {
+    let mut quote: Option<char> = None;
+    for (i, c) in line.char_indices() {
+        match (quote, c) {
+            (Some(q), c) if c == q => quote = None,
+            (None, '"') | (None, '\'') => quote = Some(c),
+            (None, '#') => return &line[..i],
+            _ => {}
+        }
+    }
+    line
+}
+
+/// 引号外未闭合的括号层数
+fn bracket_depth(text: &str) -> i32 {
+    let mut quote: Option<char> = None;
+    let mut depth = 0;
+    for c in text.chars() {
+        match (quote, c) {
+            (Some(q), c) if c == q => quote = None,
+            (None, '"') | (None, '\'') => quote = Some(c),
+            (None, '(' | '[' | '{') => depth += 1,
+            (None, ')' | ']' | '}') => depth -= 1,
+            _ => {}
+        }
+    }
+    depth
+}
+
+/// 去掉注释并把跨行的语句（未闭合的括号、行尾的 `,` 或 `\`）合并成一行，返回起始行号（从 0 开始）
+fn logical_lines(content: &str) -> Result<Vec<(usize, String)>, String> {
+    let mut lines = Vec::new();
+    let mut pending: Option<(usize, String)> = None;
+    for (index, raw) in content.lines().enumerate() {
+        let line = strip_comment(raw).trim();
+        let (start, mut text) = match pending.take() {
+            Some((start, text)) if line.is_empty() => {
+                pending = Some((start, text));
+                continue;
+            }
+            Some((start, text)) => (start, format!("{} {}", text, line)),
+            None => (index, line.to_string()),
+        };
+        let continued = text.ends_with('\\');
+        if continued {
+            text.pop();
+        }
+        if continued || text.ends_with(',') || bracket_depth(&text) > 0 {
+            pending = Some((start, text.trim_end().to_string()));
+        } else {
+            lines.push((start, text));
+        }
+    }
+    if let Some((start, _)) = pending {
+        return Err(format!(
+            "Unterminated statement starting on Brewfile line {}",
+            start + 1
+        ));
+    }
+    Ok(lines)
+}
+
+/// 求值 Brewfile 中的条件；只识别 OS.mac? / OS.linux?，其他条件返回 None
+fn eval_condition(condition: &str) -> Option<bool> {
+    let condition = condition.trim();
+    let condition = condition.strip_suffix(" then").unwrap_or(condition).trim();
+    let (negate, condition) = match condition.strip_prefix('!') {
+        Some(rest) => (true, rest.trim()),
+        None => (false, condition),
+    };
+    let value = match condition {
+        "OS.mac?" => cfg!(target_os = "macos"),
+        "OS.linux?" => cfg!(target_os = "linux"),
+        _ => return None,
+    };
+    Some(value != negate)
+}
+
+/// `if` / `case` / `do` 等代码块
+struct Branch {
+    /// 外层代码块是否生效
+    parent_active: bool,
+    active: bool,
+    /// 条件无法求值时各分支都按生效处理
+    known: bool,
+    /// 已有分支成立，后续 elsif / else 不再生效
+    taken: bool,
+}
+
+impl Branch {
+    fn open(parent_active: bool, condition: Option<bool>) -> Self {
+        Self {
+            parent_active,
+            active: parent_active && condition.unwrap_or(true),
+            known: condition.is_some(),
+            taken: condition == Some(true),
+        }
+    }
+    
+    fn next(&mut self, condition: Option<bool>) {
+        if self.known && self.taken {
+            self.active = false;
+            return;
+        }
+        self.known = condition.is_some();
+        self.taken = condition == Some(true);
+        self.active = self.parent_active && condition.unwrap_or(true);
+    }
+}
+
+/// 把 `cask_args` 和条目自身的 `args:` 合并成一个哈希，条目自身的同名参数优先
+fn merge_cask_args(cask_args: &BTreeMap<String, String>, own: Option<&String>) -> String {
+    let mut merged = cask_args.clone();
+    if let Some(own) = own {
+        let inner = own.trim().trim_start_matches('{').trim_end_matches('}');
+        for item in split_top_level(inner) {
+            if let Some((key, value)) = item.split_once(':') {
+                merged.insert(key.trim().to_string(), value.trim().to_string());
+            }
+        }
+    }
+    let items: Vec<String> = merged
+        .iter()
+        .map(|(key, value)| format!("{}: {}", key, value))
+        .collect();
+    format!("{{ {} }}", items.join(", "))
+}
+
+/// 解析 Brewfile 文本
+///
+/// Brewfile 是 Ruby 代码：`if OS.mac?` / `OS.linux?` 条件按当前系统求值，其他条件的各分支都保留，
+/// 其余无法识别的语句忽略
+pub(crate) fn parse_brewfile(content: &str) -> Result<Vec<BrewfileEntry>, String> {
+    let mut entries = Vec::new();
+    let mut branches: Vec<Branch> = Vec::new();
+    let mut cask_args: BTreeMap<String, String> = BTreeMap::new();
+    
+    for (index, text) in logical_lines(content)? {
+        let mut line = text.as_str();
+        if line.is_empty() {
+            continue;
+        }
+        let active = branches.last().is_none_or(|b| b.active);
+        
+        let (keyword, condition) = line.split_once(char::is_whitespace).unwrap_or((line, ""));
+        match keyword {
+            "if" | "unless" => {
+                let value = eval_condition(condition).map(|v| v != (keyword == "unless"));
+                branches.push(Branch::open(active, value));
+                continue;
+            }
+            "elsif" | "when" | "else" => {
+                let value = if keyword == "elsif" {
+                    eval_condition(condition)
+                } else {
+                    None
+                };
+                if let Some(branch) = branches.last_mut() {
+                    match keyword {
+                        "else" if branch.known => {
+                            let taken = branch.taken;
+                            branch.next(Some(!taken));
+                        }
+                        _ => branch.next(value),
+                    }
+                }
+                continue;
+            }
+            "case" | "begin" => {
+                branches.push(Branch::open(active, None));
+                continue;
+            }
+            "end" => {
+                branches.pop();
+                continue;
+            }
+            _ if line.ends_with(" do") || line.contains(" do |") => {
+                branches.push(Branch::open(active, None));
+                continue;
+            }
+            _ => {}
+        }
+        if !active {
+            continue;
+        }
+        
+        // 行尾的 `if OS.mac?` / `unless OS.linux?` 修饰
+        for (modifier, negate) in [(" if ", false), (" unless ", true)] {
+            if let Some((statement, condition)) = line.rsplit_once(modifier) {
+                if !condition.contains(['"', '\'']) {
+                    if eval_condition(condition).map(|v| v != negate) == Some(false) {
+                        line = "";
+                    } else {
+                        line = statement.trim();
+                    }
+                    break;
+                }
+            }
+        }
+        
+        // 没有参数的语句（如 `cask_args` 之外的 Ruby 代码）无法对应到条目，忽略
+        let Some((kind, rest)) = line.split_once(char::is_whitespace) else {
+            continue;
+        };
+        let rest = rest.trim().trim_start_matches('(').trim_end_matches(')');
+        
+        if kind == "cask_args" {
+            let inner = rest.trim().trim_start_matches('{').trim_end_matches('}');
+            for part in split_top_level(inner) {
+                if let Some((key, value)) = part.split_once(':') {
+                    cask_args.insert(key.trim().to_string(), value.trim().to_string());
+                }
+            }
+            continue;
+        }
+        
+        let mut parts = split_top_level(rest).into_iter();
+        let name = parts
+            .next()
+            .map(|n| unquote(&n))
+            .filter(|n| !n.is_empty())
+            .ok_or_else(|| format!("Missing name on Brewfile line {}", index + 1))?;
+        
+        let mut options = BTreeMap::new();
+        for (position, part) in parts.enumerate() {
+            if part.starts_with(['"', '\'']) {
+                // tap "user/repo", "https://..."
+                if kind == "tap" && position == 0 {
+                    options.insert("url".to_string(), unquote(&part));
+                }
+            } else if let Some((key, value)) =
+                part.split_once(':').filter(|(k, _)| !k.trim().is_empty())
+            {
+                options.insert(key.trim().to_string(), value.trim().to_string());
+            } else if let Some((key, value)) = part.split_once("=>") {
+                options.insert(
+                    key.trim().trim_start_matches(':').to_string(),
+                    value.trim().to_string(),
+                );
+            }
+        }
+        if kind == "cask" && !cask_args.is_empty() {
+            let args = merge_cask_args(&cask_args, options.get("args"));
+            options.insert("args".to_string(), args);
+        }
+        
+        entries.push(BrewfileEntry {
+            kind: kind.to_string(),
+            name,
+            options,
+            line: index + 1,
+        });
+    }
+    
+    Ok(entries)
+}
+
+/// 把 `["with-foo", "HEAD"]` 形式的数组或 `{ appdir: "~/Apps" }` 形式的哈希转成命令行参数
+fn option_flags(raw: &str) -> Vec<String> {
+    let raw = raw.trim();
+    let inner = raw
+        .trim_start_matches(['[', '{'])
+        .trim_end_matches([']', '}']);
+    
+    split_top_level(inner)
+        .into_iter()
+        .filter(|item| !item.is_empty())
+        .map(|item| {
+            if raw.starts_with('{') {
+                let (key, value) = item.split_once(':').unwrap_or((&item, "true"));
+                let key = key.trim().replace('_', "-");
+                match unquote(value).as_str() {
+                    "true" => format!("--{}", key),
+                    value => format!("--{}={}", key, value),
+                }
+            } else {
+                format!("--{}", unquote(&item).trim_start_matches("--"))
+            }
+        })
+        .collect()
+}
+
+/// 已安装的 App Store 应用 ID（mas list）；未安装 mas 时返回 None
+fn mas_installed_ids() -> Option<Vec<String>> {
+    let output = Command::new("mas").arg("list").output().ok()?;
+    if !output.status.success() {
+        return None;
+    }
+    
+    Some(
+        String::from_utf8_lossy(&output.stdout)
+            .lines()
+            .filter_map(|line| line.split_whitespace().next())
+            .map(|id| id.to_string())
+            .collect(),
+    )
+}
+
+fn read_brewfile(path: &str) -> Result<Vec<BrewfileEntry>, String> {
+    let content =
+        std::fs::read_to_string(path).map_err(|e| format!("Failed to read Brewfile: {}", e))?;
+    parse_brewfile(&content)
+}
+
+/// 对比 Brewfile 条目与当前安装状态
+pub(crate) fn plan_entries(
+    path: &str,
+    entries: Vec<BrewfileEntry>,
+) -> Result<BrewfilePlan, String> {
+    let state = installed_state()?;
+    let taps = installed_taps()?;
+    let leaves = installed_leaves(false)?;
+    let mas_ids = if entries.iter().any(|e| e.kind == "mas") {
+        mas_installed_ids()
+    } else {
+        None
+    };
+    
+    let mut to_install = Vec::new();
+    let mut to_upgrade = Vec::new();
+    let mut skipped = Vec::new();
+    
+    for entry in &entries {
+        match entry.kind.as_str() {
+            "tap" => {
+                if !taps.contains(&entry.name) {
+                    to_install.push(entry.clone());
+                }
+            }
+            "brew" => {
+                let installed = state.formulae.iter().find(|f| {
+                    f.full_name == entry.name
+                        || f.name == entry.name
+                        || f.name == entry.short_name()
+                });
+                match installed {
+                    None => to_install.push(entry.clone()),
+                    Some(f) if f.outdated => to_upgrade.push(entry.clone()),
+                    Some(_) => {}
+                }
+            }
+            "cask" => {
+                let installed = state.casks.iter().find(|c| {
+                    c.full_token == entry.name
+                        || c.token == entry.name
+                        || c.token == entry.short_name()
+                });
+                match installed {
+                    None => to_install.push(entry.clone()),
+                    Some(c) if c.outdated => to_upgrade.push(entry.clone()),
+                    Some(_) => {}
+                }
+            }
+            "mas" => match (&mas_ids, entry.option("id")) {
+                (Some(ids), Some(id)) => {
+                    if !ids.iter().any(|i| i == id) {
+                        to_install.push(entry.clone());
+                    }
+                }
+                _ => skipped.push(entry.clone()),
+            },
+            _ => skipped.push(entry.clone()),
+        }
+    }
+    
+    let listed = |kind: &str, name: &str, full_name: &str| {
+        entries.iter().any(|e| {
+            e.kind == kind && (e.name == name || e.name == full_name || e.short_name() == name)
+        })
+    };
+    // 列出的 cask 所依赖的 formula 和 cask（depends_on）虽未列出也要保留
+    let cask_dependencies: Vec<&str> = state
+        .casks
+        .iter()
+        .filter(|c| listed("cask", &c.token, &c.full_token))
+        .flat_map(|c| {
+            ["formula", "cask"]
+                .into_iter()
+                .filter_map(|kind| c.depends_on.get(kind).and_then(|v| v.as_array()))
+                .flatten()
+                .filter_map(|v| v.as_str())
+        })
+        .collect();
+    let required = |name: &str, full_name: &str| {
+        cask_dependencies.iter().any(|d| {
+            *d == name || *d == full_name || d.rsplit('/').next() == Some(name)
+        })
+    };
+    let mut extra_installed: Vec<ExtraPackage> = state
+        .formulae
+        .iter()
+        .filter(|f| leaves.contains(&f.name) || leaves.contains(&f.full_name))
+        .filter(|f| !listed("brew", &f.name, &f.full_name))
+        .filter(|f| !required(&f.name, &f.full_name))
+        .map(|f| ExtraPackage {
+            name: f.name.clone(),
+            pkg_type: "formula".to_string(),
+        })
+        .collect();
+    extra_installed.extend(
+        state
+            .casks
+            .iter()
+            .filter(|c| !listed("cask", &c.token, &c.full_token))
+            .filter(|c| !required(&c.token, &c.full_token))
+            .map(|c| ExtraPackage {
+                name: c.token.clone(),
+                pkg_type: "cask".to_string(),
+            }),
+    );
+    
+    Ok(BrewfilePlan {
+        path: path.to_string(),
+        entries,
+        to_install,
+        to_upgrade,
+        extra_installed,
+        skipped,
+    })
+}
+
+/// 解析 Brewfile 并与当前安装状态对比
+#[tauri::command]
+pub async fn plan_brewfile(path: String) -> Result<BrewfilePlan, String> {
+    let entries = read_brewfile(&path)?;
+    plan_entries(&path, entries)
+}
+
+/// 生成条目的安装或升级命令
+fn entry_args(entry: &BrewfileEntry, upgrade: bool) -> Vec<String> {
+    match entry.kind.as_str() {
+        "tap" => {
+            let mut args = vec!["tap".to_string(), entry.name.clone()];
+            if let Some(url) = entry.option("url") {
+                args.push(url.to_string());
+            }
+            args
+        }
+        "cask" => {
+            let subcommand = if upgrade { "upgrade" } else { "install" };
+            let mut args = vec![
+                subcommand.to_string(),
+                "--cask".to_string(),
+                entry.name.clone(),
+            ];
+            if !upgrade {
+                if let Some(raw) = entry.option("args") {
+                    args.extend(option_flags(raw));
+                }
+            }
+            args
+        }
+        _ => {
+            let subcommand = if upgrade { "upgrade" } else { "install" };
+            let mut args = vec![subcommand.to_string(), entry.name.clone()];
+            if !upgrade {
+                if let Some(raw) = entry.option("args") {
+                    args.extend(option_flags(raw));
+                }
+            }
+            args
+        }
+    }
+}
+
+/// 安装或升级 formula 后的附加步骤：link 和 restart_service
+async fn formula_post_steps(
+    queue: &QueueGuard,
+    entry: &BrewfileEntry,
+    window: &tauri::Window,
+    output: &mut CommandOutput,
+) -> Result<(), String> {
+    let name = entry.short_name().to_string();
+    
+    match entry.option("link") {
+        Some("true") => {
+            let args = vec!["link".to_string(), "--force".to_string(), name.clone()];
+            output.append(
+                execute_brew_command_queued(queue, &args, window, &name, "apply_brewfile").await?,
+            );
+        }
+        Some("false") => {
+            let args = vec!["unlink".to_string(), name.clone()];
+            output.append(
+                execute_brew_command_queued(queue, &args, window, &name, "apply_brewfile").await?,
+            );
+        }
+        _ => {}
+    }
+    
+    if entry
+        .option("restart_service")
+        .is_some_and(|v| v != "false")
+    {
+        let args = vec!["services".to_string(), "restart".to_string(), name.clone()];
+        output.append(
+            execute_brew_command_queued(queue, &args, window, &name, "apply_brewfile").await?,
+        );
+    }
+    
+    Ok(())
+}
+
+/// 用 mas 安装 App Store 应用，同样在操作队列中执行
+fn mas_install(
+    _queue: &QueueGuard,
+    entry: &BrewfileEntry,
+    window: &tauri::Window,
+) -> Result<CommandOutput, String> {
+    let id = entry.option("id").unwrap_or_default();
+    let started_at = history::now_millis();
+    let output = Command::new("mas")
+        .args(["install", id])
+        .output()
+        .map_err(|e| format!("Failed to execute mas: {}", e))?;
+    
+    let output = CommandOutput {
+        success: output.status.success(),
+        stdout: String::from_utf8_lossy(&output.stdout).to_string(),
+        stderr: String::from_utf8_lossy(&output.stderr).to_string(),
+        exit_code: output.status.code().unwrap_or(-1),
+    };
+    let argv = vec!["mas".to_string(), "install".to_string(), id.to_string()];
+    history::record_external(
+        window.app_handle(),
+        "apply_brewfile",
+        &entry.name,
+        argv,
+        started_at,
+        &output,
+    );
+    
+    Ok(output)
+}
+
+/// 执行单个条目的安装或升级，formula 成功后继续处理 link 和 restart_service
+async fn run_entry(
+    queue: &QueueGuard,
+    entry: &BrewfileEntry,
+    upgrade: bool,
+    window: &tauri::Window,
+) -> Result<CommandOutput, String> {
+    if entry.kind == "mas" {
+        return mas_install(queue, entry, window);
+    }
+    
+    let args = entry_args(entry, upgrade);
+    let mut output =
+        execute_brew_command_queued(queue, &args, window, &entry.name, "apply_brewfile").await?;
+    if output.success && entry.kind == "brew" {
+        formula_post_steps(queue, entry, window, &mut output).await?;
+    }
+    
+    Ok(output)
+}
+
+fn entry_result(
+    entry: &BrewfileEntry,
+    action: &str,
+    output: Result<CommandOutput, String>,
+) -> BrewfileEntryResult {
+    let (success, message) = match output {
+        Ok(o) if o.success => (true, String::new()),
+        Ok(o) => (
+            false,
+            o.stderr.lines().last().unwrap_or_default().to_string(),
+        ),
+        Err(e) => (false, e),
+    };
+    BrewfileEntryResult {
+        kind: entry.kind.clone(),
+        name: entry.name.clone(),
+        action: action.to_string(),
+        success,
+        message,
+    }
+}
+
+/// 执行 Brewfile 计划：添加 tap、安装和升级条目，可选地卸载未列出的包
+#[tauri::command]
+pub async fn apply_brewfile(
+    path: String,
+    options: Option<ApplyBrewfileOptions>,
+    window: tauri::Window,
+) -> Result<Vec<BrewfileEntryResult>, String> {
+    let options = options.unwrap_or_default();
+    let entries = read_brewfile(&path)?;
+    // 整个 Brewfile 执行期间独占操作队列，开始前拍一次快照（也覆盖 mas 安装）
+    let queue = operations::enter_queue();
+    auto_snapshot(window.app_handle(), "apply_brewfile", &path);
+    let plan = plan_entries(&path, entries)?;
+    let mut results = Vec::new();
+    
+    // tap 必须先于其中的包
+    let (taps, packages): (Vec<_>, Vec<_>) = plan.to_install.iter().partition(|e| e.kind == "tap");
+    
+    for entry in taps {
+        let output = run_entry(&queue, entry, false, &window).await;
+        results.push(entry_result(entry, "tap", output));
+    }
+    
+    for entry in packages {
+        let output = run_entry(&queue, entry, false, &window).await;
+        results.push(entry_result(entry, "install", output));
+    }
+    
+    if !options.no_upgrade {
+        for entry in &plan.to_upgrade {
+            let output = run_entry(&queue, entry, true, &window).await;
+            results.push(entry_result(entry, "upgrade", output));
+        }
+    }
+    
+    if options.cleanup {
+        for extra in &plan.extra_installed {
+            let is_cask = extra.pkg_type == "cask";
+            let args = package_args("uninstall", &extra.name, is_cask);
+            let output =
+                execute_brew_command_queued(&queue, &args, &window, &extra.name, "apply_brewfile")
+                    .await;
+            let entry = BrewfileEntry {
+                kind: if is_cask { "cask" } else { "brew" }.to_string(),
+                name: extra.name.clone(),
+                options: BTreeMap::new(),
+                line: 0,
+            };
+            results.push(entry_result(&entry, "uninstall", output));
+        }
+    }
+    
+    Ok(results)
+}
@@ -0,0 +1,350 @@
+use serde::Serialize;
+use serde_json::Value;
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+
+use crate::disk::{measure_path, walker_threads, DirSize};
+use crate::homebrew::{execute_brew_command, get_brew_prefix};
+
+/// 单个 cask 产物的大小
+#[derive(Debug, Clone, Serialize)]
+pub struct ArtifactSize {
+    /// 产物类型：app、binary、pkg、font、caskroom 等
+    pub kind: String,
+    /// 安装位置；pkg 为安装包回执 ID
+    pub path: String,
+    pub apparent: u64,
+    pub allocated: u64,
+    pub files: u64,
+    pub error: Option<String>,
+}
+
+/// cask 的大小明细
+#[derive(Debug, Clone, Serialize)]
+pub struct CaskSizeBreakdown {
+    pub token: String,
+    pub appdir: String,
+    pub apparent: u64,
+    pub allocated: u64,
+    pub files: u64,
+    pub artifacts: Vec<ArtifactSize>,
+}
+
+/// 产物类型对应的默认安装目录（相对于用户主目录的以 `~/` 开头）
+fn default_dir(kind: &str) -> Option<&'static str> {
+    Some(match kind {
+        "app" | "suite" => "/Applications",
+        "font" => "~/Library/Fonts",
+        "prefpane" => "~/Library/PreferencePanes",
+        "qlplugin" => "~/Library/QuickLook",
+        "mdimporter" => "~/Library/Spotlight",
+        "colorpicker" => "~/Library/ColorPickers",
+        "dictionary" => "~/Library/Dictionaries",
+        "service" => "~/Library/Services",
+        "input_method" => "~/Library/Input Methods",
+        "internet_plugin" => "~/Library/Internet Plug-Ins",
+        "audio_unit_plugin" => "~/Library/Audio/Plug-Ins/Components",
+        "vst_plugin" => "~/Library/Audio/Plug-Ins/VST",
+        "vst3_plugin" => "~/Library/Audio/Plug-Ins/VST3",
+        "screen_saver" => "~/Library/Screen Savers",
+        "keyboard_layout" => "/Library/Keyboard Layouts",
+        _ => return None,
+    })
+}
+
+fn expand_home(path: &str) -> PathBuf {
+    match (path.strip_prefix("~/"), std::env::var("HOME")) {
+        (Some(rest), Ok(home)) => Path::new(&home).join(rest),
+        _ => PathBuf::from(path),
+    }
+}
+
+/// 从 HOMEBREW_CASK_OPTS 中读取 `--<kind>dir` 选项，例如 `--appdir=~/Applications`
+fn cask_opt_dir(opts: &str, kind: &str) -> Option<String> {
+    let flag = format!("--{}dir", kind.replace('_', "-"));
+    let mut parts = opts.split_whitespace();
+    while let Some(part) = parts.next() {
+        if let Some(value) = part.strip_prefix(&flag) {
+            if let Some(value) = value.strip_prefix('=') {
+                return Some(value.trim_matches('"').to_string());
+            }
+            if value.is_empty() {
+                return parts.next().map(|v| v.trim_matches('"').to_string());
+            }
+        }
+    }
+    None
+}
+
+/// 读取安装时保存的 cask 配置（Caskroom/<token>/.metadata/config.json）
+fn installed_config(prefix: &str, token: &str) -> Value {
+    let path = Path::new(prefix)
+        .join("Caskroom")
+        .join(token)
+        .join(".metadata/config.json");
+    std::fs::read_to_string(path)
+        .ok()
+        .and_then(|content| serde_json::from_str(&content).ok())
+        .unwrap_or(Value::Null)
+}
+
+/// 安装配置中的 `<kind>dir`；命令行参数（explicit）优先于安装时的 HOMEBREW_CASK_OPTS（env）
+fn config_dir(config: &Value, kind: &str) -> Option<String> {
+    let key = format!("{}dir", kind);
+    ["explicit", "env"]
+        .iter()
+        .find_map(|scope| config[*scope][key.as_str()].as_str())
+        .map(String::from)
+}
+
+/// 产物的安装目录：显式传入的 appdir > 安装时的配置 > HOMEBREW_CASK_OPTS > 默认目录
+fn artifact_dir(kind: &str, appdir: Option<&str>, config: &Value) -> Option<PathBuf> {
+    let kind = if kind == "suite" { "app" } else { kind };
+    if kind == "app" {
+        if let Some(dir) = appdir {
+            return Some(expand_home(dir));
+        }
+    }
+    if let Some(dir) = config_dir(config, kind) {
+        return Some(expand_home(&dir));
+    }
+    let opts = std::env::var("HOMEBREW_CASK_OPTS").unwrap_or_default();
+    cask_opt_dir(&opts, kind)
+        .as_deref()
+        .or_else(|| default_dir(kind))
+        .map(expand_home)
+}
+
+/// 拆出产物的源文件名和 target
+fn source_and_target(value: &Value) -> Option<(String, Option<String>)> {
+    let items = value.as_array()?;
+    let source = items.first()?.as_str()?.to_string();
+    let target = items
+        .iter()
+        .skip(1)
+        .find_map(|v| v.get("target").and_then(|t| t.as_str()))
+        .map(String::from);
+    Some((source, target))
+}
+
+fn file_name(path: &str) -> String {
+    Path::new(path)
+        .file_name()
+        .map(|n| n.to_string_lossy().to_string())
+        .unwrap_or_else(|| path.to_string())
+}
+
+/// 产物的安装位置；app 在配置目录找不到时，再尝试 /Applications 和 ~/Applications
+fn artifact_location(
+    kind: &str,
+    value: &Value,
+    prefix: &str,
+    appdir: Option<&str>,
+    config: &Value,
+) -> Option<PathBuf> {
+    let (source, target) = source_and_target(value)?;
+    let name = target.unwrap_or_else(|| file_name(&source));
+    if name.starts_with('/') || name.starts_with("~/") {
+        return Some(expand_home(&name));
+    }
+    
+    match kind {
+        "binary" => Some(Path::new(prefix).join("bin").join(name)),
+        "manpage" => {
+            let section = name.rsplit('.').next().unwrap_or("1");
+            Some(
+                Path::new(prefix)
+                    .join("share/man")
+                    .join(format!("man{}", section))
+                    .join(name),
+            )
+        }
+        "app" | "suite" => {
+            let mut candidates: Vec<PathBuf> =
+                artifact_dir(kind, appdir, config).into_iter().collect();
+            candidates.push(PathBuf::from("/Applications"));
+            candidates.push(expand_home("~/Applications"));
+            let candidates: Vec<PathBuf> = candidates.into_iter().map(|d| d.join(&name)).collect();
+            candidates
+                .iter()
+                .find(|p| p.symlink_metadata().is_ok())
+                .or(candidates.first())
+                .cloned()
+        }
+        _ => artifact_dir(kind, appdir, config).map(|dir| dir.join(name)),
+    }
+}
+
+/// uninstall 段中的 pkgutil 回执 ID（可以是正则）
+fn pkgutil_ids(artifacts: &[Value]) -> Vec<String> {
+    let mut ids = Vec::new();
+    for artifact in artifacts {
+        let Some(stanzas) = artifact.get("uninstall").and_then(|v| v.as_array()) else {
+            continue;
+        };
+        for stanza in stanzas {
+            match stanza.get("pkgutil") {
+                Some(Value::String(id)) => ids.push(id.clone()),
+                Some(Value::Array(list)) => {
+                    ids.extend(list.iter().filter_map(|v| v.as_str()).map(String::from))
+                }
+                _ => {}
+            }
+        }
+    }
+    ids.sort();
+    ids.dedup();
+    ids
+}
+
+fn run_pkgutil(args: &[&str]) -> Result<String, String> {
+    let output = std::process::Command::new("pkgutil")
+        .args(args)
+        .output()
+        .map_err(|e| format!("Failed to execute pkgutil: {}", e))?;
+    if !output.status.success() {
+        return Err(String::from_utf8_lossy(&output.stderr).trim().to_string());
+    }
+    Ok(String::from_utf8_lossy(&output.stdout).to_string())
+}
+
+/// 按安装包回执统计文件大小；目录可能与其他软件共享，只计文件
+fn measure_pkg_receipt(pattern: &str) -> Result<DirSize, String> {
+    let mut size = DirSize::default();
+    let pkgs = run_pkgutil(&[&format!("--pkgs={}", pattern)])?;
+    let mut found = false;
+    for id in pkgs.lines().map(str::trim).filter(|l| !l.is_empty()) {
+        found = true;
+        let info = run_pkgutil(&["--pkg-info", id])?;
+        let field = |key: &str| {
+            info.lines()
+                .find_map(|l| l.strip_prefix(key))
+                .map(|v| v.trim().to_string())
+                .unwrap_or_default()
+        };
+        let root = Path::new(&field("volume:")).join(field("location:"));
+        for file in run_pkgutil(&["--files", id, "--only-files"])?.lines() {
+            if let Ok(measured) = measure_path(&root.join(file), 1) {
+                size.apparent += measured.apparent;
+                size.allocated += measured.allocated;
+                size.files += measured.files;
+            }
+        }
+    }
+    if !found {
+        return Err(format!("No package receipt matches {}", pattern));
+    }
+    Ok(size)
+}
+
+fn artifact_size(kind: &str, path: String, measured: Result<DirSize, String>) -> ArtifactSize {
+    let size = measured.as_ref().copied().unwrap_or_default();
+    ArtifactSize {
+        kind: kind.to_string(),
+        path,
+        apparent: size.apparent,
+        allocated: size.allocated,
+        files: size.files,
+        error: measured.err(),
+    }
+}
+
+/// 根据 cask JSON 中的产物列表计算大小
+pub(crate) fn measure_cask(
+    prefix: &str,
+    token: &str,
+    artifacts: &[Value],
+    appdir: Option<&str>,
+    threads: usize,
+) -> CaskSizeBreakdown {
+    let measure = |path: &Path| {
+        measure_path(path, threads)
+            .map_err(|e| format!("Failed to measure {}: {}", path.display(), e))
+    };
+    
+    let config = installed_config(prefix, token);
+    let mut sizes = Vec::new();
+    for artifact in artifacts {
+        let Some(object) = artifact.as_object() else {
+            continue;
+        };
+        for (kind, value) in object {
+            if kind != "binary" && kind != "manpage" && default_dir(kind).is_none() {
+                continue;
+            }
+            if let Some(path) = artifact_location(kind, value, prefix, appdir, &config) {
+                sizes.push(artifact_size(
+                    kind,
+                    path.to_string_lossy().to_string(),
+                    measure(&path),
+                ));
+            }
+        }
+    }
+    
+    if cfg!(target_os = "macos") {
+        for id in pkgutil_ids(artifacts) {
+            let measured = measure_pkg_receipt(&id);
+            sizes.push(artifact_size("pkg", id, measured));
+        }
+    }
+    
+    let caskroom = Path::new(prefix).join("Caskroom").join(token);
+    sizes.push(artifact_size(
+        "caskroom",
+        caskroom.to_string_lossy().to_string(),
+        measure(&caskroom),
+    ));
+    
+    CaskSizeBreakdown {
+        token: token.to_string(),
+        appdir: artifact_dir("app", appdir, &config)
+            .map(|d| d.to_string_lossy().to_string())
+            .unwrap_or_default(),
+        apparent: sizes.iter().map(|s| s.apparent).sum(),
+        allocated: sizes.iter().map(|s| s.allocated).sum(),
+        files: sizes.iter().map(|s| s.files).sum(),
+        artifacts: sizes,
+    }
+}
+
+/// 读取一组 cask 的产物列表（brew info --cask --json=v2）
+pub(crate) fn cask_artifacts(tokens: &[&str]) -> Result<HashMap<String, Vec<Value>>, String> {
+    let mut args = vec!["info", "--cask", "--json=v2"];
+    args.extend_from_slice(tokens);
+    let output = execute_brew_command(&args)?;
+    if !output.success {
+        return Err(format!("Failed to get cask info: {}", output.stderr));
+    }
+    
+    let json: Value = serde_json::from_str(&output.stdout)
+        .map_err(|e| format!("Failed to parse cask info: {}", e))?;
+    let casks = json["casks"].as_array().cloned().unwrap_or_default();
+    Ok(casks
+        .into_iter()
+        .filter_map(|cask| {
+            let token = cask["token"].as_str()?.to_string();
+            let artifacts = cask["artifacts"].as_array().cloned().unwrap_or_default();
+            Some((token, artifacts))
+        })
+        .collect())
+}
+
+/// 获取 cask 各产物的大小明细；appdir 用于覆盖安装时记录的 `--appdir`
+#[tauri::command]
+pub async fn get_cask_size_breakdown(
+    token: String,
+    appdir: Option<String>,
+) -> Result<CaskSizeBreakdown, String> {
+    let prefix = get_brew_prefix()?;
+    let artifacts = cask_artifacts(&[&token])?
+        .remove(&token)
+        .ok_or_else(|| format!("Cask not found: {}", token))?;
+    Ok(measure_cask(
+        &prefix,
+        &token,
+        &artifacts,
+        appdir.as_deref(),
+        walker_threads(),
+    ))
+}
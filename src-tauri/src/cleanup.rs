@@ -0,0 +1,236 @@
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+use std::path::Path;
+use tauri::Manager;
+
+use crate::disk::{measure_path, walker_threads};
+use crate::disk_report::logs_dir;
+use crate::homebrew::{execute_brew_command, get_brew_cache, get_brew_prefix, CommandOutput};
+use crate::operations;
+
+/// 清理预览选项
+#[derive(Debug, Default, Serialize, Deserialize)]
+#[serde(default, rename_all = "camelCase")]
+pub struct CleanupOptions {
+    /// 删除早于该天数的缓存；`all` 为全部，为空时使用 Homebrew 默认值
+    pub prune: Option<String>,
+    /// 只清理这些包；为空时全局清理
+    pub packages: Vec<String>,
+    /// 同时删除最新版本的下载缓存（-s）
+    pub scrub: bool,
+}
+
+impl CleanupOptions {
+    fn to_args(&self) -> Result<Vec<String>, String> {
+        let mut args = vec!["cleanup".to_string(), "--dry-run".to_string()];
+        if let Some(prune) = self.prune.as_deref().filter(|p| !p.is_empty()) {
+            if prune != "all" && prune.parse::<u32>().is_err() {
+                return Err(format!("Invalid prune age: {}", prune));
+            }
+            args.push(format!("--prune={}", prune));
+        }
+        if self.scrub {
+            args.push("-s".to_string());
+        }
+        args.extend(self.packages.iter().cloned());
+        Ok(args)
+    }
+}
+
+/// 一项可清理的内容
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CleanupItem {
+    pub path: String,
+    /// 占用字节数；路径存在时为实际占用，否则取 brew 输出中的估算值
+    pub size: u64,
+    pub files: Option<u64>,
+    /// old_version、stale_download、stale_lock、log 或 other
+    pub category: String,
+    pub package: Option<String>,
+}
+
+/// 清理预览
+#[derive(Debug, Clone, Serialize)]
+pub struct CleanupPlan {
+    pub items: Vec<CleanupItem>,
+    pub total_size: u64,
+    /// 其他提示，例如将要清理的失效符号链接
+    pub notes: Vec<String>,
+}
+
+/// 解析 brew 的可读大小，例如 `4.5MB`（按 1024 进位）
+fn parse_readable_size(text: &str) -> Option<u64> {
+    let text = text.trim();
+    let split = text.find(|c: char| c.is_ascii_alphabetic())?;
+    let (number, unit) = text.split_at(split);
+    let number: f64 = number.trim().parse().ok()?;
+    let factor = match unit {
+        "B" => 1.0,
+        "KB" => 1024.0,
+        "MB" => 1024.0 * 1024.0,
+        "GB" => 1024.0 * 1024.0 * 1024.0,
+        _ => return None,
+    };
+    Some((number * factor) as u64)
+}
+
+/// 解析 `Would remove: <path> (12 files, 3.4MB)` 行
+fn parse_remove_line(line: &str) -> Option<(String, Option<u64>, Option<u64>)> {
+    let rest = line.strip_prefix("Would remove: ")?.trim();
+    let Some((path, details)) = rest.strip_suffix(')').and_then(|r| r.rsplit_once(" (")) else {
+        return Some((rest.to_string(), None, None));
+    };
+    
+    let mut files = None;
+    let mut size = None;
+    for part in details.split(',').map(str::trim) {
+        if let Some(count) = part
+            .strip_suffix(" files")
+            .or_else(|| part.strip_suffix(" file"))
+        {
+            files = count.parse().ok();
+        } else {
+            size = parse_readable_size(part);
+        }
+    }
+    Some((path.to_string(), files, size))
+}
+
+/// 按路径判断类别，并尽量推断所属的包
+fn classify(path: &Path, prefix: &Path, cache: &Path, logs: &Path) -> (String, Option<String>) {
+    let file_name = path
+        .file_name()
+        .map(|n| n.to_string_lossy().to_string())
+        .unwrap_or_default();
+    
+    if file_name.ends_with(".lock") || path.components().any(|c| c.as_os_str() == "locks") {
+        let package = file_name.strip_suffix(".formula.lock").map(String::from);
+        return ("stale_lock".to_string(), package);
+    }
+    for root in ["Cellar", "Caskroom"] {
+        if let Ok(rest) = path.strip_prefix(prefix.join(root)) {
+            let package = rest.iter().next().map(|n| n.to_string_lossy().to_string());
+            return ("old_version".to_string(), package);
+        }
+    }
+    if let Ok(rest) = path.strip_prefix(logs) {
+        let package = rest.iter().next().map(|n| n.to_string_lossy().to_string());
+        return ("log".to_string(), package);
+    }
+    if path.starts_with(cache) {
+        // 缓存文件名形如 `<sha256>--name--1.2.3.tar.gz` 或 `name--1.2.3.tar.gz`
+        let mut parts = file_name.split("--");
+        let first = parts.next().unwrap_or_default();
+        let is_hash = first.len() == 64 && first.chars().all(|c| c.is_ascii_hexdigit());
+        let name = if is_hash { parts.next() } else { Some(first) };
+        let package = name
+            .filter(|n| !n.is_empty() && file_name.contains("--"))
+            .map(String::from);
+        return ("stale_download".to_string(), package);
+    }
+    ("other".to_string(), None)
+}
+
+fn run_plan(args: &[String]) -> Result<CleanupPlan, String> {
+    let prefix = get_brew_prefix()?;
+    let cache = get_brew_cache()?;
+    let logs = logs_dir();
+    let args: Vec<&str> = args.iter().map(|a| a.as_str()).collect();
+    let output = execute_brew_command(&args)?;
+    if !output.success {
+        return Err(format!("Failed to plan cleanup: {}", output.stderr));
+    }
+    
+    let mut items = Vec::new();
+    let mut notes = Vec::new();
+    for line in output
+        .stdout
+        .lines()
+        .map(str::trim)
+        .filter(|l| !l.is_empty())
+    {
+        let Some((path, files, estimated)) = parse_remove_line(line) else {
+            if line.starts_with("Would ") {
+                notes.push(line.to_string());
+            }
+            continue;
+        };
+        let path_ref = Path::new(&path);
+        let size = measure_path(path_ref, walker_threads())
+            .map(|s| s.allocated)
+            .ok()
+            .or(estimated)
+            .unwrap_or(0);
+        let (category, package) = classify(path_ref, Path::new(&prefix), Path::new(&cache), &logs);
+        items.push(CleanupItem {
+            path,
+            size,
+            files,
+            category,
+            package,
+        });
+    }
+    
+    Ok(CleanupPlan {
+        total_size: items.iter().map(|i| i.size).sum(),
+        items,
+        notes,
+    })
+}
+
+/// 预览 brew cleanup 会删除的内容（brew cleanup --dry-run）
+#[tauri::command]
+pub async fn plan_cleanup(options: Option<CleanupOptions>) -> Result<CleanupPlan, String> {
+    run_plan(&options.unwrap_or_default().to_args()?)
+}
+
+/// 只删除选中的清理项；每一项都必须仍在 brew 的清理范围内
+#[tauri::command]
+pub async fn cleanup_selected(
+    items: Vec<CleanupItem>,
+    window: tauri::Window,
+) -> Result<CommandOutput, String> {
+    // 最宽的清理范围包含了任何选项组合下的清理项
+    let widest = CleanupOptions {
+        prune: Some("all".to_string()),
+        packages: Vec::new(),
+        scrub: true,
+    };
+    let allowed: HashSet<String> = run_plan(&widest.to_args()?)?
+        .items
+        .into_iter()
+        .map(|i| i.path)
+        .collect();
+    
+    let paths = items.iter().map(|i| i.path.clone()).collect();
+    let output = operations::remove_paths(
+        window.app_handle(),
+        "cleanup_selected",
+        "cleanup",
+        paths,
+        |path| {
+            if !allowed.contains(path) {
+                return Err(format!("Skipped {}: not eligible for cleanup", path));
+            }
+            let target = Path::new(path);
+            let result = match std::fs::symlink_metadata(target) {
+                Ok(meta) if meta.is_dir() => std::fs::remove_dir_all(target),
+                Ok(_) => std::fs::remove_file(target),
+                Err(e) => Err(e),
+            };
+            result.map_err(|e| format!("Failed to remove {}: {}", path, e))
+        },
+    );
+    
+    let mut packages: Vec<&str> = items
+        .iter()
+        .filter(|i| i.category == "old_version")
+        .filter_map(|i| i.package.as_deref())
+        .collect();
+    packages.sort();
+    packages.dedup();
+    for package in packages {
+        crate::disk::invalidate_sizes(window.app_handle(), Some(package));
+    }
+    Ok(output)
+}
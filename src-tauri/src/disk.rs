@@ -0,0 +1,441 @@
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+use std::fs::Metadata;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Condvar, Mutex};
+use std::time::UNIX_EPOCH;
+use tauri::{AppHandle, Emitter, Manager};
+
+use crate::cask_artifacts;
+use crate::homebrew::get_brew_prefix;
+use crate::storage;
+
+const SIZE_CACHE_FILE: &str = "package_sizes.json";
+
+/// 目录占用空间
+#[derive(Debug, Default, Clone, Copy, Serialize, Deserialize)]
+pub struct DirSize {
+    /// 文件长度之和
+    pub apparent: u64,
+    /// 实际占用的磁盘块
+    pub allocated: u64,
+    pub files: u64,
+}
+
+/// 单个包的大小，`get_all_package_sizes` 逐个通过 `package-size` 事件发送
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PackageSize {
+    pub name: String,
+    #[serde(rename = "type")]
+    pub pkg_type: String,
+    pub apparent: u64,
+    pub allocated: u64,
+    pub error: Option<String>,
+}
+
+/// 并行遍历使用的线程数
+pub(crate) fn walker_threads() -> usize {
+    std::thread::available_parallelism()
+        .map(|n| n.get())
+        .unwrap_or(4)
+        .min(8)
+}
+
+#[cfg(unix)]
+fn allocated_bytes(meta: &Metadata) -> u64 {
+    use std::os::unix::fs::MetadataExt;
+    meta.blocks() * 512
+}
+
+#[cfg(not(unix))]
+fn allocated_bytes(meta: &Metadata) -> u64 {
+    meta.len()
+}
+
+/// 有多个硬链接的文件返回 (设备, inode)，用于去重
+#[cfg(unix)]
+fn hardlink_key(meta: &Metadata) -> Option<(u64, u64)> {
+    use std::os::unix::fs::MetadataExt;
+    (meta.nlink() > 1 && !meta.is_dir()).then(|| (meta.dev(), meta.ino()))
+}
+
+#[cfg(not(unix))]
+fn hardlink_key(_meta: &Metadata) -> Option<(u64, u64)> {
+    None
+}
+
+struct Totals {
+    apparent: AtomicU64,
+    allocated: AtomicU64,
+    files: AtomicU64,
+    seen: Mutex<HashSet<(u64, u64)>>,
+}
+
+impl Totals {
+    fn account(&self, meta: &Metadata) {
+        if let Some(key) = hardlink_key(meta) {
+            if let Ok(mut seen) = self.seen.lock() {
+                if !seen.insert(key) {
+                    return;
+                }
+            }
+        }
+        self.apparent.fetch_add(meta.len(), Ordering::Relaxed);
+        self.allocated
+            .fetch_add(allocated_bytes(meta), Ordering::Relaxed);
+        if !meta.is_dir() {
+            self.files.fetch_add(1, Ordering::Relaxed);
+        }
+    }
+}
+
+struct WalkQueue {
+    dirs: Vec<PathBuf>,
+    /// 正在处理目录的线程数；队列为空且为 0 时遍历结束
+    active: usize,
+}
+
+/// 计算路径占用的空间：多线程遍历，不跟随符号链接，硬链接只计一次
+pub(crate) fn measure_path(path: &Path, threads: usize) -> std::io::Result<DirSize> {
+    let root = std::fs::symlink_metadata(path)?;
+    let totals = Totals {
+        apparent: AtomicU64::new(0),
+        allocated: AtomicU64::new(0),
+        files: AtomicU64::new(0),
+        seen: Mutex::new(HashSet::new()),
+    };
+    totals.account(&root);
+    
+    if root.is_dir() {
+        let queue = Mutex::new(WalkQueue {
+            dirs: vec![path.to_path_buf()],
+            active: 0,
+        });
+        let ready = Condvar::new();
+        
+        std::thread::scope(|scope| {
+            for _ in 0..threads.max(1) {
+                scope.spawn(|| walk_worker(&queue, &ready, &totals));
+            }
+        });
+    }
+    
+    Ok(DirSize {
+        apparent: totals.apparent.into_inner(),
+        allocated: totals.allocated.into_inner(),
+        files: totals.files.into_inner(),
+    })
+}
+
+fn walk_worker(queue: &Mutex<WalkQueue>, ready: &Condvar, totals: &Totals) {
+    loop {
+        let dir = {
+            let Ok(mut state) = queue.lock() else {
+                return;
+            };
+            loop {
+                if let Some(dir) = state.dirs.pop() {
+                    state.active += 1;
+                    break dir;
+                }
+                if state.active == 0 {
+                    ready.notify_all();
+                    return;
+                }
+                state = match ready.wait(state) {
+                    Ok(state) => state,
+                    Err(_) => return,
+                };
+            }
+        };
+        
+        let mut subdirs = Vec::new();
+        if let Ok(entries) = std::fs::read_dir(&dir) {
+            for entry in entries.filter_map(|e| e.ok()) {
+                // DirEntry::metadata 不跟随符号链接
+                let Ok(meta) = entry.metadata() else {
+                    continue;
+                };
+                totals.account(&meta);
+                if meta.is_dir() {
+                    subdirs.push(entry.path());
+                }
+            }
+        }
+        
+        if let Ok(mut state) = queue.lock() {
+            state.dirs.extend(subdirs);
+            state.active -= 1;
+        }
+        ready.notify_all();
+    }
+}
+
+/// 并行计算多个路径的大小，每个路径单线程遍历；无法读取的路径记为 0
+pub(crate) fn measure_many(paths: &[PathBuf]) -> Vec<DirSize> {
+    let next = AtomicU64::new(0);
+    let results = Mutex::new(vec![DirSize::default(); paths.len()]);
+    std::thread::scope(|scope| {
+        for _ in 0..walker_threads().min(paths.len()) {
+            scope.spawn(|| loop {
+                let index = next.fetch_add(1, Ordering::Relaxed) as usize;
+                let Some(path) = paths.get(index) else {
+                    return;
+                };
+                let size = measure_path(path, 1).unwrap_or_default();
+                if let Ok(mut results) = results.lock() {
+                    results[index] = size;
+                }
+            });
+        }
+    });
+    results.into_inner().unwrap_or_default()
+}
+
+/// 包大小缓存中的一项
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct CachedSize {
+    /// 包目录及其 keg、安装回执的修改时间，任一变化即失效
+    stamp: String,
+    size: DirSize,
+}
+
+#[derive(Default)]
+struct SizeCacheState {
+    loaded: bool,
+    dirty: bool,
+    entries: HashMap<String, CachedSize>,
+}
+
+/// 持久化的包大小缓存，键为 `formula:name` / `cask:name`
+#[derive(Default)]
+pub struct SizeCache(Mutex<SizeCacheState>);
+
+fn cache_key(name: &str, is_cask: bool) -> String {
+    format!("{}:{}", if is_cask { "cask" } else { "formula" }, name)
+}
+
+fn modified_nanos(path: &Path) -> u128 {
+    std::fs::symlink_metadata(path)
+        .and_then(|m| m.modified())
+        .ok()
+        .and_then(|t| t.duration_since(UNIX_EPOCH).ok())
+        .map(|d| d.as_nanos())
+        .unwrap_or(0)
+}
+
+/// 由目录、其直接子目录（keg）和安装回执的修改时间组成的标记
+fn paths_stamp(paths: &[PathBuf]) -> String {
+    let mut parts = Vec::new();
+    for path in paths {
+        parts.push(format!(
+            "{}={}",
+            path.to_string_lossy(),
+            modified_nanos(path)
+        ));
+        let Ok(entries) = std::fs::read_dir(path) else {
+            continue;
+        };
+        let mut children: Vec<String> = entries
+            .filter_map(|e| e.ok())
+            .map(|e| {
+                let child = e.path();
+                let receipt = modified_nanos(&child.join("INSTALL_RECEIPT.json"));
+                format!(
+                    "{}={}/{}",
+                    e.file_name().to_string_lossy(),
+                    modified_nanos(&child),
+                    receipt
+                )
+            })
+            .collect();
+        children.sort();
+        parts.extend(children);
+    }
+    parts.join(";")
+}
+
+fn with_cache<T>(app: &AppHandle, f: impl FnOnce(&mut SizeCacheState) -> T) -> Option<T> {
+    let cache = app.state::<SizeCache>();
+    let mut state = cache.0.lock().ok()?;
+    if !state.loaded {
+        state.entries = storage::read_json(app, SIZE_CACHE_FILE).unwrap_or_default();
+        state.loaded = true;
+    }
+    Some(f(&mut state))
+}
+
+/// 把有变化的包大小缓存写回磁盘
+pub(crate) fn save_size_cache(app: &AppHandle) {
+    let entries = with_cache(app, |state| {
+        let dirty = std::mem::take(&mut state.dirty);
+        dirty.then(|| state.entries.clone())
+    })
+    .flatten();
+    if let Some(entries) = entries {
+        let _ = storage::write_json(app, SIZE_CACHE_FILE, &entries);
+    }
+}
+
+/// 安装、升级、卸载等操作后使缓存失效；package 为 None 时清空全部
+pub(crate) fn invalidate_sizes(app: &AppHandle, package: Option<&str>) {
+    with_cache(app, |state| {
+        match package {
+            Some(name) => {
+                state.entries.remove(&cache_key(name, false));
+                state.entries.remove(&cache_key(name, true));
+            }
+            None => state.entries.clear(),
+        }
+        state.dirty = true;
+    });
+    save_size_cache(app);
+}
+
+/// 决定缓存是否失效的目录：formula 为 Cellar 下的 keg，cask 为 Caskroom 下的版本目录
+fn package_dir(prefix: &str, name: &str, is_cask: bool) -> PathBuf {
+    let root = if is_cask { "Caskroom" } else { "Cellar" };
+    Path::new(prefix).join(root).join(name)
+}
+
+fn cached_size(app: &AppHandle, key: &str, stamp: &str) -> Option<DirSize> {
+    with_cache(app, |state| {
+        state
+            .entries
+            .get(key)
+            .filter(|c| c.stamp == stamp)
+            .map(|c| c.size)
+    })
+    .flatten()
+}
+
+/// 计算单个包的大小；目录未变化时直接使用缓存
+pub(crate) fn measure_package(
+    app: &AppHandle,
+    prefix: &str,
+    name: &str,
+    is_cask: bool,
+    threads: usize,
+) -> Result<DirSize, String> {
+    measure_package_with(app, prefix, name, is_cask, threads, None)
+}
+
+/// cask 大小按产物列表计算；artifacts 为空时单独查询
+fn measure_package_with(
+    app: &AppHandle,
+    prefix: &str,
+    name: &str,
+    is_cask: bool,
+    threads: usize,
+    artifacts: Option<&[serde_json::Value]>,
+) -> Result<DirSize, String> {
+    let dir = package_dir(prefix, name, is_cask);
+    if !dir.exists() {
+        return Err(format!("No installation found for {}", name));
+    }
+    let key = cache_key(name, is_cask);
+    let stamp = paths_stamp(std::slice::from_ref(&dir));
+    if let Some(size) = cached_size(app, &key, &stamp) {
+        return Ok(size);
+    }
+    
+    let size = if is_cask {
+        let breakdown = match artifacts {
+            Some(artifacts) => cask_artifacts::measure_cask(prefix, name, artifacts, None, threads),
+            None => {
+                let artifacts = cask_artifacts::cask_artifacts(&[name])?
+                    .remove(name)
+                    .unwrap_or_default();
+                cask_artifacts::measure_cask(prefix, name, &artifacts, None, threads)
+            }
+        };
+        DirSize {
+            apparent: breakdown.apparent,
+            allocated: breakdown.allocated,
+            files: breakdown.files,
+        }
+    } else {
+        measure_path(&dir, threads).map_err(|e| format!("Failed to measure {}: {}", name, e))?
+    };
+    
+    with_cache(app, |state| {
+        state.entries.insert(key, CachedSize { stamp, size });
+        state.dirty = true;
+    });
+    
+    Ok(size)
+}
+
+/// 一次计算所有已安装包的大小，每算完一个发送一次 `package-size` 事件
+#[tauri::command]
+pub async fn get_all_package_sizes(window: tauri::Window) -> Result<Vec<PackageSize>, String> {
+    let prefix = get_brew_prefix()?;
+    let app = window.app_handle();
+    
+    let mut jobs: Vec<(String, bool)> = Vec::new();
+    for (dir, is_cask) in [("Cellar", false), ("Caskroom", true)] {
+        if let Ok(entries) = std::fs::read_dir(Path::new(&prefix).join(dir)) {
+            for entry in entries.filter_map(|e| e.ok()) {
+                if entry.path().is_dir() {
+                    jobs.push((entry.file_name().to_string_lossy().to_string(), is_cask));
+                }
+            }
+        }
+    }
+    
+    // 缓存失效的 cask 一次性查询产物列表
+    let stale_casks: Vec<&str> = jobs
+        .iter()
+        .filter(|(name, is_cask)| {
+            *is_cask && {
+                let stamp = paths_stamp(&[package_dir(&prefix, name, true)]);
+                cached_size(app, &cache_key(name, true), &stamp).is_none()
+            }
+        })
+        .map(|(name, _)| name.as_str())
+        .collect();
+    let artifacts = if stale_casks.is_empty() {
+        HashMap::new()
+    } else {
+        cask_artifacts::cask_artifacts(&stale_casks).unwrap_or_default()
+    };
+    
+    // 包之间并行，每个包内部单线程遍历
+    let jobs = Mutex::new(jobs);
+    let results = Mutex::new(Vec::new());
+    std::thread::scope(|scope| {
+        for _ in 0..walker_threads() {
+            scope.spawn(|| loop {
+                let Some((name, is_cask)) = jobs.lock().ok().and_then(|mut j| j.pop()) else {
+                    return;
+                };
+                let measured = measure_package_with(
+                    app,
+                    &prefix,
+                    &name,
+                    is_cask,
+                    1,
+                    artifacts.get(&name).map(|a| a.as_slice()),
+                );
+                let size = PackageSize {
+                    pkg_type: if is_cask { "cask" } else { "formula" }.to_string(),
+                    apparent: measured.as_ref().map(|s| s.apparent).unwrap_or(0),
+                    allocated: measured.as_ref().map(|s| s.allocated).unwrap_or(0),
+                    error: measured.err(),
+                    name,
+                };
+                let _ = window.emit("package-size", &size);
+                if let Ok(mut results) = results.lock() {
+                    results.push(size);
+                }
+            });
+        }
+    });
+    
+    save_size_cache(app);
+    
+    let mut results = results.into_inner().map_err(|e| e.to_string())?;
+    results.sort_by(|a, b| a.name.cmp(&b.name));
+    Ok(results)
+}
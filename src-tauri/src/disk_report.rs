@@ -0,0 +1,426 @@
+use serde::Serialize;
+use std::collections::{BTreeSet, HashMap, HashSet};
+use std::path::{Path, PathBuf};
+
+use crate::brewfile::{installed_state, InstalledStateJson};
+use crate::cask_artifacts::measure_cask;
+use crate::disk::{measure_many, measure_path, walker_threads, DirSize};
+use crate::homebrew::{get_brew_cache, get_brew_prefix};
+use crate::versions::list_kegs;
+
+/// 一个目录的占用
+#[derive(Debug, Clone, Serialize)]
+pub struct DiskCategory {
+    pub path: String,
+    pub apparent: u64,
+    pub allocated: u64,
+    pub files: u64,
+}
+
+impl DiskCategory {
+    fn new(path: &Path, size: DirSize) -> Self {
+        Self {
+            path: path.to_string_lossy().to_string(),
+            apparent: size.apparent,
+            allocated: size.allocated,
+            files: size.files,
+        }
+    }
+}
+
+/// Cellar 中的一个 keg
+#[derive(Debug, Clone, Serialize)]
+pub struct KegUsage {
+    pub version: String,
+    pub path: String,
+    pub apparent: u64,
+    pub allocated: u64,
+    /// opt 链接指向的当前版本
+    pub current: bool,
+}
+
+/// Cellar 中的一个 formula
+#[derive(Debug, Clone, Serialize)]
+pub struct CellarPackageUsage {
+    pub name: String,
+    pub apparent: u64,
+    pub allocated: u64,
+    pub versions: Vec<KegUsage>,
+}
+
+/// 一个 cask：Caskroom 暂存目录和全部产物（应用、pkg 等）的占用
+#[derive(Debug, Clone, Serialize)]
+pub struct CaskUsage {
+    pub name: String,
+    pub caskroom_allocated: u64,
+    pub apparent: u64,
+    pub allocated: u64,
+}
+
+/// 下载缓存按内容分类
+#[derive(Debug, Clone, Serialize)]
+pub struct CacheUsage {
+    pub path: String,
+    pub allocated: u64,
+    /// 源码包、cask 安装包等下载文件
+    pub downloads: DiskCategory,
+    pub bottles: DiskCategory,
+    /// api/ 下的 formula、cask JSON
+    pub api: DiskCategory,
+    pub other: DiskCategory,
+}
+
+/// `brew cleanup` 可以删除的旧版本 keg
+#[derive(Debug, Clone, Serialize)]
+pub struct OldKeg {
+    pub name: String,
+    pub version: String,
+    pub path: String,
+    pub allocated: u64,
+}
+
+/// 删除一个叶子包及其孤立依赖后可释放的空间
+#[derive(Debug, Clone, Serialize)]
+pub struct LeafClosure {
+    pub name: String,
+    /// 叶子包本身和会变成孤立的依赖
+    pub formulae: Vec<String>,
+    pub allocated: u64,
+}
+
+/// Homebrew 的整体磁盘占用
+#[derive(Debug, Clone, Serialize)]
+pub struct DiskReport {
+    pub prefix: String,
+    pub allocated: u64,
+    pub cellar: DiskCategory,
+    pub cellar_packages: Vec<CellarPackageUsage>,
+    pub caskroom: DiskCategory,
+    pub casks: Vec<CaskUsage>,
+    pub cache: CacheUsage,
+    pub logs: DiskCategory,
+    pub var: DiskCategory,
+    pub old_kegs: Vec<OldKeg>,
+    pub old_kegs_allocated: u64,
+    pub leaves: Vec<LeafClosure>,
+}
+
+fn sum_sizes(sizes: &[DirSize]) -> DirSize {
+    sizes.iter().fold(DirSize::default(), |acc, s| DirSize {
+        apparent: acc.apparent + s.apparent,
+        allocated: acc.allocated + s.allocated,
+        files: acc.files + s.files,
+    })
+}
+
+fn child_dirs(path: &Path) -> Vec<String> {
+    let mut names: Vec<String> = std::fs::read_dir(path)
+        .map(|entries| {
+            entries
+                .filter_map(|e| e.ok())
+                .filter(|e| e.path().is_dir())
+                .map(|e| e.file_name().to_string_lossy().to_string())
+                .collect()
+        })
+        .unwrap_or_default();
+    names.sort();
+    names
+}
+
+/// Homebrew 日志目录（HOMEBREW_LOGS 或平台默认位置）
+pub(crate) fn logs_dir() -> PathBuf {
+    if let Ok(dir) = std::env::var("HOMEBREW_LOGS") {
+        return PathBuf::from(dir);
+    }
+    let home = std::env::var("HOME").unwrap_or_default();
+    if cfg!(target_os = "macos") {
+        Path::new(&home).join("Library/Logs/Homebrew")
+    } else {
+        Path::new(&home).join(".cache/Homebrew/Logs")
+    }
+}
+
+/// 缓存条目的分类：根目录下的符号链接指向 downloads/，不重复计算
+fn cache_kind(path: &Path) -> &'static str {
+    let name = path
+        .file_name()
+        .map(|n| n.to_string_lossy().to_string())
+        .unwrap_or_default();
+    if path.is_dir() {
+        "other"
+    } else if name.contains(".bottle") {
+        "bottles"
+    } else if name.ends_with(".json") {
+        "api"
+    } else {
+        "downloads"
+    }
+}
+
+fn cache_usage(cache: &Path, logs: &Path) -> CacheUsage {
+    let mut groups: HashMap<&'static str, Vec<PathBuf>> = HashMap::new();
+    let entries = std::fs::read_dir(cache)
+        .map(|entries| entries.filter_map(|e| e.ok()).map(|e| e.path()).collect())
+        .unwrap_or_else(|_| Vec::new());
+    for path in entries {
+        if path.is_symlink() || path == logs {
+            continue;
+        }
+        match path.file_name().and_then(|n| n.to_str()) {
+            Some("api") => groups.entry("api").or_default().push(path),
+            Some("downloads") => {
+                if let Ok(downloads) = std::fs::read_dir(&path) {
+                    for entry in downloads.filter_map(|e| e.ok()) {
+                        let entry = entry.path();
+                        groups.entry(cache_kind(&entry)).or_default().push(entry);
+                    }
+                }
+            }
+            _ => groups.entry(cache_kind(&path)).or_default().push(path),
+        }
+    }
+    
+    let mut category = |kind: &str| {
+        let paths = groups.remove(kind).unwrap_or_default();
+        let path = if kind == "api" {
+            cache.join("api")
+        } else {
+            cache.to_path_buf()
+        };
+        DiskCategory::new(&path, sum_sizes(&measure_many(&paths)))
+    };
+    let downloads = category("downloads");
+    let bottles = category("bottles");
+    let api = category("api");
+    let other = category("other");
+    
+    CacheUsage {
+        path: cache.to_string_lossy().to_string(),
+        allocated: downloads.allocated + bottles.allocated + api.allocated + other.allocated,
+        downloads,
+        bottles,
+        api,
+        other,
+    }
+}
+
+fn cellar_usage(prefix: &str) -> Vec<CellarPackageUsage> {
+    let cellar = Path::new(prefix).join("Cellar");
+    let mut kegs: Vec<(String, String, PathBuf)> = Vec::new();
+    for name in child_dirs(&cellar) {
+        for version in list_kegs(prefix, &name) {
+            let path = cellar.join(&name).join(&version);
+            kegs.push((name.clone(), version, path));
+        }
+    }
+    let paths: Vec<PathBuf> = kegs.iter().map(|(_, _, path)| path.clone()).collect();
+    let sizes = measure_many(&paths);
+    
+    let mut packages: Vec<CellarPackageUsage> = Vec::new();
+    for ((name, version, path), size) in kegs.into_iter().zip(sizes) {
+        if packages.last().is_none_or(|p| p.name != name) {
+            packages.push(CellarPackageUsage {
+                name: name.clone(),
+                apparent: 0,
+                allocated: 0,
+                versions: Vec::new(),
+            });
+        }
+        let current = std::fs::read_link(Path::new(prefix).join("opt").join(&name))
+            .ok()
+            .and_then(|target| {
+                target
+                    .file_name()
+                    .map(|n| n.to_string_lossy() == version.as_str())
+            })
+            .unwrap_or(false);
+        if let Some(package) = packages.last_mut() {
+            package.apparent += size.apparent;
+            package.allocated += size.allocated;
+            package.versions.push(KegUsage {
+                version,
+                path: path.to_string_lossy().to_string(),
+                apparent: size.apparent,
+                allocated: size.allocated,
+                current,
+            });
+        }
+    }
+    packages
+}
+
+/// 除当前版本外的 keg；已锁定或没有 opt 链接的 formula 不清理
+fn old_kegs(prefix: &str, packages: &[CellarPackageUsage]) -> Vec<OldKeg> {
+    let pinned_dir = Path::new(prefix).join("var/homebrew/pinned");
+    packages
+        .iter()
+        .filter(|p| !pinned_dir.join(&p.name).exists() && p.versions.iter().any(|v| v.current))
+        .flat_map(|p| {
+            p.versions.iter().filter(|v| !v.current).map(|v| OldKeg {
+                name: p.name.clone(),
+                version: v.version.clone(),
+                path: v.path.clone(),
+                allocated: v.allocated,
+            })
+        })
+        .collect()
+}
+
+/// 从 start 出发能到达的所有 formula（含 start 本身）
+fn reach<'a>(deps: &HashMap<&'a str, Vec<&'a str>>, start: &[&'a str]) -> HashSet<&'a str> {
+    let mut seen: HashSet<&str> = HashSet::new();
+    let mut stack: Vec<&str> = start.to_vec();
+    while let Some(name) = stack.pop() {
+        if seen.insert(name) {
+            stack.extend(deps.get(name).into_iter().flatten().copied());
+        }
+    }
+    seen
+}
+
+/// 每个叶子包的依赖闭包：只被它（间接）依赖、删除后会成为孤立的 formula
+fn leaf_closures(state: &InstalledStateJson, packages: &[CellarPackageUsage]) -> Vec<LeafClosure> {
+    let mut canonical: HashMap<&str, &str> = HashMap::new();
+    for formula in &state.formulae {
+        canonical.insert(&formula.name, &formula.name);
+        canonical.insert(&formula.full_name, &formula.name);
+    }
+    let deps: HashMap<&str, Vec<&str>> = state
+        .formulae
+        .iter()
+        .map(|f| {
+            let deps = f
+                .dependencies
+                .iter()
+                .filter_map(|d| canonical.get(d.as_str()).copied())
+                .collect();
+            (f.name.as_str(), deps)
+        })
+        .collect();
+    
+    let required: HashSet<&str> = deps.values().flatten().copied().collect();
+    let mut roots: Vec<&str> = state
+        .formulae
+        .iter()
+        .filter(|f| f.installed.iter().any(|v| v.installed_on_request))
+        .map(|f| f.name.as_str())
+        .collect();
+    for cask in &state.casks {
+        let formulae = cask.depends_on["formula"]
+            .as_array()
+            .cloned()
+            .unwrap_or_default();
+        for formula in formulae.iter().filter_map(|f| f.as_str()) {
+            if let Some(name) = canonical.get(formula) {
+                roots.push(name);
+            }
+        }
+    }
+    let sizes: HashMap<&str, u64> = packages
+        .iter()
+        .map(|p| (p.name.as_str(), p.allocated))
+        .collect();
+    
+    let mut closures: Vec<LeafClosure> = state
+        .formulae
+        .iter()
+        .map(|f| f.name.as_str())
+        .filter(|name| !required.contains(name))
+        .map(|leaf| {
+            let others: Vec<&str> = roots.iter().copied().filter(|r| *r != leaf).collect();
+            let keep = reach(&deps, &others);
+            let formulae: BTreeSet<&str> = reach(&deps, &[leaf])
+                .into_iter()
+                .filter(|name| *name == leaf || !keep.contains(name))
+                .collect();
+            LeafClosure {
+                name: leaf.to_string(),
+                allocated: formulae.iter().filter_map(|name| sizes.get(name)).sum(),
+                formulae: formulae.into_iter().map(String::from).collect(),
+            }
+        })
+        .collect();
+    closures.sort_by(|a, b| {
+        b.allocated
+            .cmp(&a.allocated)
+            .then_with(|| a.name.cmp(&b.name))
+    });
+    closures
+}
+
+/// 获取 Homebrew 的磁盘占用明细
+#[tauri::command]
+pub async fn get_disk_report() -> Result<DiskReport, String> {
+    let prefix = get_brew_prefix()?;
+    let state = installed_state()?;
+    let measure = |path: &Path| measure_path(path, walker_threads()).unwrap_or_default();
+    
+    let mut cellar_packages = cellar_usage(&prefix);
+    let cellar_path = Path::new(&prefix).join("Cellar");
+    let cellar = DiskCategory::new(&cellar_path, measure(&cellar_path));
+    let old_kegs = old_kegs(&prefix, &cellar_packages);
+    let leaves = leaf_closures(&state, &cellar_packages);
+    cellar_packages.sort_by(|a, b| {
+        b.allocated
+            .cmp(&a.allocated)
+            .then_with(|| a.name.cmp(&b.name))
+    });
+    
+    let caskroom_path = Path::new(&prefix).join("Caskroom");
+    let caskroom = DiskCategory::new(&caskroom_path, measure(&caskroom_path));
+    let mut casks: Vec<CaskUsage> = state
+        .casks
+        .iter()
+        .map(|cask| {
+            let breakdown = measure_cask(
+                &prefix,
+                &cask.token,
+                &cask.artifacts,
+                None,
+                walker_threads(),
+            );
+            CaskUsage {
+                name: cask.token.clone(),
+                caskroom_allocated: breakdown
+                    .artifacts
+                    .iter()
+                    .filter(|a| a.kind == "caskroom")
+                    .map(|a| a.allocated)
+                    .sum(),
+                apparent: breakdown.apparent,
+                allocated: breakdown.allocated,
+            }
+        })
+        .collect();
+    casks.sort_by(|a, b| {
+        b.allocated
+            .cmp(&a.allocated)
+            .then_with(|| a.name.cmp(&b.name))
+    });
+    
+    let logs_path = logs_dir();
+    let logs = DiskCategory::new(&logs_path, measure(&logs_path));
+    let cache_path = get_brew_cache()?;
+    let cache = cache_usage(Path::new(&cache_path), &logs_path);
+    let var_path = Path::new(&prefix).join("var");
+    let var = DiskCategory::new(&var_path, measure(&var_path));
+    
+    Ok(DiskReport {
+        allocated: cellar.allocated
+            + caskroom.allocated
+            + cache.allocated
+            + logs.allocated
+            + var.allocated,
+        old_kegs_allocated: old_kegs.iter().map(|k| k.allocated).sum(),
+        prefix,
+        cellar,
+        cellar_packages,
+        caskroom,
+        casks,
+        cache,
+        logs,
+        var,
+        old_kegs,
+        leaves,
+    })
+}
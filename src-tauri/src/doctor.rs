@@ -0,0 +1,173 @@
+use serde::{Deserialize, Serialize};
+
+use crate::homebrew::execute_brew_command;
+
+/// `brew doctor` 的一条警告
+#[derive(Debug, Serialize, Deserialize)]
+pub struct DoctorFinding {
+    /// unbrewed_files / links / toolchain / path / permissions / deprecated / repository / other
+    pub category: String,
+    pub title: String,
+    pub detail: String,
+    /// 警告中给出的修复命令（如有）
+    pub suggested_command: Option<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct DoctorReport {
+    /// 没有任何警告（"Your system is ready to brew."）
+    pub ready: bool,
+    pub findings: Vec<DoctorFinding>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ConfigEntry {
+    pub key: String,
+    pub value: String,
+}
+
+/// `brew config` 的解析结果
+#[derive(Debug, Serialize, Deserialize)]
+pub struct BrewConfig {
+    /// 按原始顺序排列的全部字段
+    pub entries: Vec<ConfigEntry>,
+    pub prefix: Option<String>,
+    pub core_tap_revision: Option<String>,
+    pub clt: Option<String>,
+    pub xcode: Option<String>,
+    pub ruby: Option<String>,
+    pub cpu: Option<String>,
+}
+
+/// 根据标题关键字给警告分类
+fn categorize_finding(title: &str) -> &'static str {
+    let lower = title.to_lowercase();
+    if lower.contains("unbrewed") || lower.contains("dylib") || lower.contains("header") {
+        "unbrewed_files"
+    } else if lower.contains("xcode") || lower.contains("command line tools") {
+        "toolchain"
+    } else if lower.contains("link") {
+        "links"
+    } else if lower.contains("path") {
+        "path"
+    } else if lower.contains("writable") || lower.contains("permission") || lower.contains("owned")
+    {
+        "permissions"
+    } else if lower.contains("deprecated") || lower.contains("disabled") {
+        "deprecated"
+    } else if lower.contains("tap") || lower.contains("git") || lower.contains("origin") {
+        "repository"
+    } else {
+        "other"
+    }
+}
+
+/// 在警告正文中查找建议执行的命令
+fn find_suggested_command(detail: &str) -> Option<String> {
+    const COMMAND_PREFIXES: [&str; 7] = [
+        "brew ",
+        "sudo ",
+        "rm ",
+        "xcode-select ",
+        "softwareupdate ",
+        "git ",
+        "export ",
+    ];
+    
+    detail
+        .lines()
+        .filter(|line| line.starts_with(' ') || line.starts_with('\t'))
+        .map(|line| line.trim())
+        .find(|line| COMMAND_PREFIXES.iter().any(|p| line.starts_with(p)))
+        .map(|line| line.to_string())
+}
+
+/// 把 `brew doctor` 的输出解析为结构化警告
+fn parse_doctor_output(output: &str) -> Vec<DoctorFinding> {
+    let mut blocks: Vec<Vec<&str>> = Vec::new();
+    
+    for line in output.lines() {
+        if line.starts_with("Warning: ") || line.starts_with("Error: ") {
+            blocks.push(vec![line]);
+        } else if let Some(block) = blocks.last_mut() {
+            block.push(line);
+        }
+    }
+    
+    blocks
+        .into_iter()
+        .map(|block| {
+            let title = block[0]
+                .trim_start_matches("Warning: ")
+                .trim_start_matches("Error: ")
+                .trim()
+                .to_string();
+            let detail = block[1..].join("\n").trim_end().to_string();
+            DoctorFinding {
+                category: categorize_finding(&title).to_string(),
+                suggested_command: find_suggested_command(&detail),
+                title,
+                detail,
+            }
+        })
+        .collect()
+}
+
+/// 运行 brew doctor 并返回结构化诊断结果
+#[tauri::command]
+pub async fn run_doctor() -> Result<DoctorReport, String> {
+    // 有警告时 brew doctor 以非零状态退出，警告输出在 stderr
+    let output = execute_brew_command(&["doctor"])?;
+    let combined = format!("{}\n{}", output.stdout, output.stderr);
+    let findings = parse_doctor_output(&combined);
+    
+    if !output.success && findings.is_empty() {
+        return Err(format!("brew doctor failed: {}", output.stderr));
+    }
+    
+    Ok(DoctorReport {
+        ready: findings.is_empty(),
+        findings,
+    })
+}
+
+/// 把 `brew config` 的 `KEY: value` 输出解析为字段
+fn parse_config_output(output: &str) -> BrewConfig {
+    let entries: Vec<ConfigEntry> = output
+        .lines()
+        .filter_map(|line| line.split_once(": "))
+        .map(|(key, value)| ConfigEntry {
+            key: key.trim().to_string(),
+            value: value.trim().to_string(),
+        })
+        .collect();
+    
+    let find = |key: &str| {
+        entries
+            .iter()
+            .find(|e| e.key.eq_ignore_ascii_case(key))
+            .map(|e| e.value.clone())
+    };
+    
+    BrewConfig {
+        prefix: find("HOMEBREW_PREFIX"),
+        core_tap_revision: find("Core tap HEAD").or_else(|| find("Core tap JSON")),
+        clt: find("CLT"),
+        xcode: find("Xcode"),
+        ruby: find("Homebrew Ruby"),
+        cpu: find("CPU"),
+        entries,
+    }
+}
+
+/// 获取 brew config 信息
+#[tauri::command]
+pub async fn get_brew_config() -> Result<BrewConfig, String> {
+    let output = execute_brew_command(&["config"])?;
+    
+    if !output.success {
+        return Err(format!("Failed to get brew config: {}", output.stderr));
+    }
+    
+    Ok(parse_config_output(&output.stdout))
+}
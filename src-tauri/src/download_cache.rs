@@ -0,0 +1,517 @@
+use serde::Serialize;
+use std::collections::HashMap;
+use std::io::Write;
+use std::path::{Component, Path, PathBuf};
+use std::process::{Command, Stdio};
+use std::time::UNIX_EPOCH;
+use tauri::Manager;
+
+use crate::disk::measure_path;
+use crate::disk_report::logs_dir;
+use crate::history;
+use crate::homebrew::{get_brew_cache, get_brew_prefix, CommandOutput};
+use crate::operations;
+use crate::versions::{list_kegs, parse_bottle_file_name};
+
+/// 下载缓存中的一个文件
+#[derive(Debug, Clone, Serialize)]
+pub struct CacheEntry {
+    pub path: String,
+    pub file_name: String,
+    /// bottle、manifest、source、cask、api 或 other
+    pub kind: String,
+    pub package: Option<String>,
+    pub version: Option<String>,
+    pub is_cask: bool,
+    pub size: u64,
+    /// 修改时间（毫秒）
+    pub modified: u64,
+    pub installed_versions: Vec<String>,
+    /// current（对应已安装版本）、superseded（已安装其他版本）、uninstalled 或 unknown
+    pub status: String,
+}
+
+/// 导出的缓存包
+#[derive(Debug, Clone, Serialize)]
+pub struct CacheBundle {
+    pub path: String,
+    pub files: Vec<String>,
+    pub size: u64,
+}
+
+const ARCHIVE_EXTENSIONS: &[&str] = &[
+    ".tar.gz", ".tar.xz", ".tar.bz2", ".tar.zst", ".tar.lz", ".tgz", ".tbz", ".txz", ".tar",
+    ".zip", ".dmg", ".pkg", ".gem", ".jar", ".xip", ".7z", ".rar", ".gz", ".bz2", ".xz",
+];
+
+/// 去掉下载文件名前的 sha256 前缀
+fn strip_hash(file_name: &str) -> &str {
+    match file_name.split_once("--") {
+        Some((hash, rest)) if hash.len() == 64 && hash.chars().all(|c| c.is_ascii_hexdigit()) => {
+            rest
+        }
+        _ => file_name,
+    }
+}
+
+/// 从 `name--1.2.3.tar.gz` 形式的文件名中解析出包名和版本
+fn parse_download_name(file_name: &str) -> Option<(String, String)> {
+    if let Some(parsed) = parse_bottle_file_name(file_name) {
+        return Some(parsed);
+    }
+    let (name, rest) = strip_hash(file_name).split_once("--")?;
+    let rest = rest.strip_suffix(".bottle_manifest.json").unwrap_or(rest);
+    let version = ARCHIVE_EXTENSIONS
+        .iter()
+        .find_map(|ext| rest.strip_suffix(ext))
+        .unwrap_or(rest);
+    Some((name.to_string(), version.to_string()))
+}
+
+fn entry_kind(file_name: &str, relative: &Path, is_cask: bool) -> &'static str {
+    if relative.starts_with("api") {
+        "api"
+    } else if file_name.contains(".bottle_manifest") || file_name.ends_with(".json") {
+        "manifest"
+    } else if file_name.contains(".bottle.") {
+        "bottle"
+    } else if is_cask {
+        "cask"
+    } else if file_name.contains("--") {
+        "source"
+    } else {
+        "other"
+    }
+}
+
+fn modified_millis(meta: &std::fs::Metadata) -> u64 {
+    meta.modified()
+        .ok()
+        .and_then(|t| t.duration_since(UNIX_EPOCH).ok())
+        .map(|d| d.as_millis() as u64)
+        .unwrap_or(0)
+}
+
+/// 遍历缓存目录，返回普通文件和符号链接（链接名 -> 目标）
+fn walk_cache(cache: &Path) -> (Vec<PathBuf>, Vec<(PathBuf, PathBuf)>) {
+    let logs = logs_dir();
+    let mut files = Vec::new();
+    let mut links = Vec::new();
+    let mut dirs = vec![cache.to_path_buf()];
+    while let Some(dir) = dirs.pop() {
+        let Ok(entries) = std::fs::read_dir(&dir) else {
+            continue;
+        };
+        for entry in entries.filter_map(|e| e.ok()) {
+            let path = entry.path();
+            let Ok(file_type) = entry.file_type() else {
+                continue;
+            };
+            if file_type.is_symlink() {
+                if let Ok(target) = std::fs::canonicalize(&path) {
+                    links.push((path, target));
+                }
+            } else if file_type.is_dir() {
+                if path != logs {
+                    dirs.push(path);
+                }
+            } else {
+                files.push(path);
+            }
+        }
+    }
+    (files, links)
+}
+
+/// 已安装的版本：formula 为 Cellar 中的 keg，cask 为 Caskroom 中的版本目录
+fn installed_versions(prefix: &str, name: &str, is_cask: bool) -> Vec<String> {
+    if !is_cask {
+        return list_kegs(prefix, name);
+    }
+    let mut versions: Vec<String> =
+        std::fs::read_dir(Path::new(prefix).join("Caskroom").join(name))
+            .map(|entries| {
+                entries
+                    .filter_map(|e| e.ok())
+                    .map(|e| e.file_name().to_string_lossy().to_string())
+                    .filter(|v| !v.starts_with('.'))
+                    .collect()
+            })
+            .unwrap_or_default();
+    versions.sort();
+    versions
+}
+
+fn list_entries(cache: &Path, prefix: &str) -> Vec<CacheEntry> {
+    let canonical_cache = std::fs::canonicalize(cache).unwrap_or_else(|_| cache.to_path_buf());
+    let (files, links) = walk_cache(cache);
+    
+    // downloads/ 中的文件名带哈希且 cask 文件名不规则，优先用指向它的链接名解析
+    let mut link_names: HashMap<PathBuf, (String, bool)> = HashMap::new();
+    for (link, target) in &links {
+        let is_cask = link
+            .strip_prefix(cache)
+            .map(|r| r.starts_with("Cask"))
+            .unwrap_or(false);
+        if let Some(name) = link.file_name() {
+            link_names.insert(
+                target.clone(),
+                (name.to_string_lossy().to_string(), is_cask),
+            );
+        }
+    }
+    
+    let mut entries: Vec<CacheEntry> = files
+        .into_iter()
+        .filter_map(|path| {
+            let meta = std::fs::symlink_metadata(&path).ok()?;
+            let file_name = path.file_name()?.to_string_lossy().to_string();
+            let canonical = std::fs::canonicalize(&path).unwrap_or_else(|_| path.clone());
+            let relative = canonical
+                .strip_prefix(&canonical_cache)
+                .unwrap_or(&canonical)
+                .to_path_buf();
+            let (source_name, is_cask) = link_names
+                .get(&canonical)
+                .cloned()
+                .unwrap_or_else(|| (file_name.clone(), relative.starts_with("Cask")));
+            let kind = entry_kind(&file_name, &relative, is_cask);
+            let parsed = if kind == "api" {
+                None
+            } else {
+                parse_download_name(&source_name)
+            };
+            
+            let (package, version) = parsed.unzip();
+            let installed = package
+                .as_deref()
+                .map(|name| installed_versions(prefix, name, is_cask))
+                .unwrap_or_default();
+            let status = match (&package, &version) {
+                (None, _) | (_, None) => "unknown",
+                _ if installed.is_empty() => "uninstalled",
+                (_, Some(v))
+                    if installed
+                        .iter()
+                        .any(|i| i == v || i.starts_with(&format!("{}_", v))) =>
+                {
+                    "current"
+                }
+                _ => "superseded",
+            };
+            
+            Some(CacheEntry {
+                path: path.to_string_lossy().to_string(),
+                file_name,
+                kind: kind.to_string(),
+                size: measure_path(&path, 1)
+                    .map(|s| s.allocated)
+                    .unwrap_or(meta.len()),
+                modified: modified_millis(&meta),
+                installed_versions: installed,
+                status: status.to_string(),
+                is_cask,
+                package,
+                version,
+            })
+        })
+        .collect();
+    entries.sort_by(|a, b| {
+        a.package
+            .cmp(&b.package)
+            .then_with(|| a.file_name.cmp(&b.file_name))
+    });
+    entries
+}
+
+/// 列出下载缓存中的文件，并对应到包和版本
+#[tauri::command]
+pub async fn list_cache_entries() -> Result<Vec<CacheEntry>, String> {
+    let cache = get_brew_cache()?;
+    let prefix = get_brew_prefix()?;
+    Ok(list_entries(Path::new(&cache), &prefix))
+}
+
+/// 校验路径位于缓存目录内，返回规范化后的路径
+fn resolve_in_cache(cache: &Path, path: &str) -> Result<PathBuf, String> {
+    let canonical_cache = std::fs::canonicalize(cache)
+        .map_err(|e| format!("Failed to resolve cache directory: {}", e))?;
+    let canonical =
+        std::fs::canonicalize(path).map_err(|e| format!("Failed to resolve {}: {}", path, e))?;
+    if !canonical.starts_with(&canonical_cache) || canonical == canonical_cache {
+        return Err(format!("{} is not inside the download cache", path));
+    }
+    Ok(canonical)
+}
+
+/// 删除缓存文件以及指向它们的链接
+#[tauri::command]
+pub async fn delete_cache_entries(
+    paths: Vec<String>,
+    window: tauri::Window,
+) -> Result<CommandOutput, String> {
+    let cache = PathBuf::from(get_brew_cache()?);
+    let (_, links) = walk_cache(&cache);
+    
+    Ok(operations::remove_paths(
+        window.app_handle(),
+        "delete_cache_entries",
+        "cache",
+        paths,
+        |path| {
+            let target = resolve_in_cache(&cache, path)?;
+            if !target.is_file() {
+                return Err(format!("Skipped {}: not a file", path));
+            }
+            std::fs::remove_file(&target)
+                .map_err(|e| format!("Failed to remove {}: {}", path, e))?;
+            for (link, _) in links.iter().filter(|(_, t)| *t == target) {
+                let _ = std::fs::remove_file(link);
+            }
+            Ok(())
+        },
+    ))
+}
+
+fn run_tar(args: &[&str]) -> Result<String, String> {
+    run_tar_with_input(args, None)
+}
+
+/// 运行 tar，可选地把 input 写入它的标准输入
+fn run_tar_with_input(args: &[&str], input: Option<&str>) -> Result<String, String> {
+    let mut child = Command::new("tar")
+        .args(args)
+        .stdin(if input.is_some() {
+            Stdio::piped()
+        } else {
+            Stdio::null()
+        })
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .map_err(|e| format!("Failed to execute tar: {}", e))?;
+    if let (Some(input), Some(mut stdin)) = (input, child.stdin.take()) {
+        stdin
+            .write_all(input.as_bytes())
+            .map_err(|e| format!("Failed to write to tar: {}", e))?;
+    }
+    let output = child
+        .wait_with_output()
+        .map_err(|e| format!("Failed to wait for tar: {}", e))?;
+    if !output.status.success() {
+        return Err(format!(
+            "tar failed: {}",
+            String::from_utf8_lossy(&output.stderr).trim()
+        ));
+    }
+    Ok(String::from_utf8_lossy(&output.stdout).to_string())
+}
+
+/// 检查解压到暂存目录的条目：只允许普通文件、目录和指向暂存目录内部的相对符号链接；
+/// 返回文件和链接相对于暂存目录的路径
+fn staged_entries(root: &Path) -> Result<Vec<PathBuf>, String> {
+    let canonical_root = std::fs::canonicalize(root)
+        .map_err(|e| format!("Failed to resolve staging directory: {}", e))?;
+    let mut entries = Vec::new();
+    let mut dirs = vec![root.to_path_buf()];
+    while let Some(dir) = dirs.pop() {
+        let listing = std::fs::read_dir(&dir)
+            .map_err(|e| format!("Failed to read {}: {}", dir.display(), e))?;
+        for entry in listing.filter_map(|e| e.ok()) {
+            let path = entry.path();
+            let relative = path.strip_prefix(root).unwrap_or(&path).to_path_buf();
+            let file_type = std::fs::symlink_metadata(&path)
+                .map_err(|e| format!("Failed to inspect {}: {}", relative.display(), e))?
+                .file_type();
+            if file_type.is_dir() {
+                dirs.push(path);
+                continue;
+            }
+            if file_type.is_symlink() {
+                let target = std::fs::read_link(&path)
+                    .map_err(|e| format!("Failed to read link {}: {}", relative.display(), e))?;
+                let inside = !target.is_absolute()
+                    && std::fs::canonicalize(&path).is_ok_and(|t| t.starts_with(&canonical_root));
+                if !inside {
+                    return Err(format!(
+                        "Refusing to import link pointing outside the cache: {} -> {}",
+                        relative.display(),
+                        target.display()
+                    ));
+                }
+            } else if !file_type.is_file() {
+                return Err(format!(
+                    "Refusing to import special file: {}",
+                    relative.display()
+                ));
+            }
+            entries.push(relative);
+        }
+    }
+    entries.sort();
+    Ok(entries)
+}
+
+/// 把检查过的条目从暂存目录移动到缓存目录，覆盖同名文件
+fn move_staged(root: &Path, cache: &Path, entries: &[PathBuf]) -> Result<(), String> {
+    for relative in entries {
+        let dest = cache.join(relative);
+        if let Some(parent) = dest.parent() {
+            std::fs::create_dir_all(parent)
+                .map_err(|e| format!("Failed to create {}: {}", parent.display(), e))?;
+        }
+        if std::fs::symlink_metadata(&dest).is_ok_and(|m| !m.is_dir()) {
+            std::fs::remove_file(&dest)
+                .map_err(|e| format!("Failed to replace {}: {}", relative.display(), e))?;
+        }
+        std::fs::rename(root.join(relative), &dest)
+            .map_err(|e| format!("Failed to import {}: {}", relative.display(), e))?;
+    }
+    Ok(())
+}
+
+/// 把缓存文件（连同指向它们的链接）打包成 tar.gz，供离线机器导入；paths 为空时导出全部
+#[tauri::command]
+pub async fn export_cache_bundle(
+    paths: Vec<String>,
+    destination: String,
+) -> Result<CacheBundle, String> {
+    let cache = PathBuf::from(get_brew_cache()?);
+    let canonical_cache = std::fs::canonicalize(&cache)
+        .map_err(|e| format!("Failed to resolve cache directory: {}", e))?;
+    let (files, links) = walk_cache(&cache);
+    
+    let targets: Vec<PathBuf> = if paths.is_empty() {
+        files
+            .iter()
+            .filter_map(|f| std::fs::canonicalize(f).ok())
+            .collect()
+    } else {
+        paths
+            .iter()
+            .map(|p| resolve_in_cache(&cache, p))
+            .collect::<Result<_, _>>()?
+    };
+    if targets.is_empty() {
+        return Err("No cache entries to export".to_string());
+    }
+    
+    let mut relative: Vec<String> = Vec::new();
+    let mut size = 0;
+    for target in &targets {
+        size += std::fs::metadata(target).map(|m| m.len()).unwrap_or(0);
+        let related = std::iter::once(target.clone()).chain(
+            links
+                .iter()
+                .filter(|(_, t)| t == target)
+                .map(|(link, _)| canonical_cache.join(link.strip_prefix(&cache).unwrap_or(link))),
+        );
+        for path in related {
+            if let Ok(rel) = path.strip_prefix(&canonical_cache) {
+                relative.push(rel.to_string_lossy().to_string());
+            }
+        }
+    }
+    relative.sort();
+    relative.dedup();
+    
+    // 文件列表通过标准输入传给 tar，避免超出命令行长度限制；
+    // 加上 `./` 前缀，以免以 `-` 开头的文件名被当成选项
+    if let Some(bad) = relative.iter().find(|r| r.contains('\n')) {
+        return Err(format!(
+            "Cannot export file with newline in its name: {}",
+            bad
+        ));
+    }
+    let list: String = relative.iter().map(|r| format!("./{}\n", r)).collect();
+    let cache_dir = canonical_cache.to_string_lossy().to_string();
+    run_tar_with_input(
+        &[
+            "-czf",
+            destination.as_str(),
+            "-C",
+            cache_dir.as_str(),
+            "-T",
+            "-",
+        ],
+        Some(&list),
+    )?;
+    
+    Ok(CacheBundle {
+        path: destination,
+        files: relative,
+        size,
+    })
+}
+
+/// 导入缓存包；拒绝包含绝对路径或 `..` 的条目、特殊文件，以及指向缓存目录之外的链接
+#[tauri::command]
+pub async fn import_cache_bundle(
+    path: String,
+    window: tauri::Window,
+) -> Result<CommandOutput, String> {
+    let cache = get_brew_cache()?;
+    let listing = run_tar(&["-tzf", &path])?;
+    let files: Vec<String> = listing
+        .lines()
+        .map(str::trim)
+        .filter(|l| !l.is_empty())
+        .map(String::from)
+        .collect();
+    if let Some(bad) = files.iter().find(|f| {
+        Path::new(f)
+            .components()
+            .any(|c| !matches!(c, Component::Normal(_) | Component::CurDir))
+    }) {
+        return Err(format!("Refusing to import unsafe bundle entry: {}", bad));
+    }
+    
+    let _queue = operations::enter_queue();
+    crate::snapshots::auto_snapshot(window.app_handle(), "import_cache_bundle", "cache");
+    let started_at = history::now_millis();
+    // 先解压到缓存目录内的暂存目录（保证可以直接移动），检查通过后再移入缓存
+    let staging = Path::new(&cache).join(format!(".import-{}", history::new_operation_id()));
+    std::fs::create_dir_all(&staging)
+        .map_err(|e| format!("Failed to create staging directory: {}", e))?;
+    let staging_dir = staging.to_string_lossy().to_string();
+    let imported = run_tar(&["-xzf", &path, "-C", &staging_dir])
+        .and_then(|_| staged_entries(&staging))
+        .and_then(|entries| {
+            move_staged(&staging, Path::new(&cache), &entries)?;
+            Ok(entries)
+        });
+    let _ = std::fs::remove_dir_all(&staging);
+    
+    let output = match imported {
+        Ok(entries) => CommandOutput {
+            success: true,
+            stdout: entries
+                .iter()
+                .map(|e| format!("Imported {}\n", e.display()))
+                .collect(),
+            stderr: String::new(),
+            exit_code: 0,
+        },
+        Err(e) => CommandOutput {
+            success: false,
+            stdout: String::new(),
+            stderr: e,
+            exit_code: 1,
+        },
+    };
+    history::record_external(
+        window.app_handle(),
+        "import_cache_bundle",
+        "cache",
+        vec![
+            "tar".to_string(),
+            "-xzf".to_string(),
+            path,
+            "-C".to_string(),
+            cache,
+        ],
+        started_at,
+        &output,
+    );
+    Ok(output)
+}
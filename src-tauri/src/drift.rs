@@ -0,0 +1,250 @@
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::sync::Mutex;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+use tauri::{AppHandle, Emitter, Manager, State};
+
+use crate::brewfile::{parse_brewfile, plan_entries, BrewfileEntry, ExtraPackage};
+use crate::storage;
+
+const TRACKED_FILE: &str = "tracked_brewfiles.json";
+/// 检查被跟踪 Brewfile 是否被修改的间隔
+const WATCH_INTERVAL: Duration = Duration::from_secs(5);
+
+/// 被跟踪的 Brewfile 与当前安装状态的偏离
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BrewfileDrift {
+    pub path: String,
+    /// Brewfile 中有但未安装的条目
+    pub missing: Vec<BrewfileEntry>,
+    /// 已安装但过时的条目
+    pub outdated: Vec<BrewfileEntry>,
+    /// 已安装但 Brewfile 中没有的包
+    pub extra: Vec<ExtraPackage>,
+    /// 读取或解析 Brewfile 失败时的错误
+    pub error: Option<String>,
+    /// 检查时间（Unix 秒）
+    pub checked_at: u64,
+}
+
+impl BrewfileDrift {
+    pub fn has_drift(&self) -> bool {
+        !self.missing.is_empty() || !self.outdated.is_empty() || !self.extra.is_empty()
+    }
+    
+    /// 用于判断偏离是否发生变化
+    fn signature(&self) -> Vec<String> {
+        self.missing
+            .iter()
+            .map(|e| format!("missing:{}", e.name))
+            .chain(self.outdated.iter().map(|e| format!("outdated:{}", e.name)))
+            .chain(self.extra.iter().map(|e| format!("extra:{}", e.name)))
+            .collect()
+    }
+}
+
+#[derive(Default)]
+struct TrackerState {
+    paths: Vec<String>,
+    /// 上次检查时的修改时间；文件不存在时为 None
+    modified: HashMap<String, Option<SystemTime>>,
+    drift: HashMap<String, BrewfileDrift>,
+    /// 已有一个后台检查在等待执行
+    refresh_queued: bool,
+}
+
+/// 被跟踪的 Brewfile 及其最近一次的偏离结果；第二个锁保证同一时间只有一次检查
+#[derive(Default)]
+pub struct BrewfileTracker(Mutex<TrackerState>, Mutex<()>);
+
+fn modified_time(path: &str) -> Option<SystemTime> {
+    std::fs::metadata(path).and_then(|m| m.modified()).ok()
+}
+
+fn compute_drift(path: &str) -> BrewfileDrift {
+    let checked_at = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0);
+    let plan = std::fs::read_to_string(path)
+        .map_err(|e| format!("Failed to read Brewfile: {}", e))
+        .and_then(|content| parse_brewfile(&content))
+        .and_then(|entries| plan_entries(path, entries));
+    
+    match plan {
+        Ok(plan) => BrewfileDrift {
+            path: path.to_string(),
+            missing: plan.to_install,
+            outdated: plan.to_upgrade,
+            extra: plan.extra_installed,
+            error: None,
+            checked_at,
+        },
+        Err(e) => BrewfileDrift {
+            path: path.to_string(),
+            missing: vec![],
+            outdated: vec![],
+            extra: vec![],
+            error: Some(e),
+            checked_at,
+        },
+    }
+}
+
+/// 重新计算所有被跟踪 Brewfile 的偏离，新出现或发生变化的偏离通过 `brewfile-drift` 事件发送
+pub(crate) fn refresh_drift(app: &AppHandle) -> Vec<BrewfileDrift> {
+    let tracker = app.state::<BrewfileTracker>();
+    let _running = tracker.1.lock().unwrap_or_else(|e| e.into_inner());
+    refresh_locked(app)
+}
+
+/// 在后台检查偏离；已有检查在排队时合并为一次
+pub(crate) fn request_refresh(app: &AppHandle) {
+    {
+        let tracker = app.state::<BrewfileTracker>();
+        let Ok(mut state) = tracker.0.lock() else {
+            return;
+        };
+        if state.refresh_queued {
+            return;
+        }
+        state.refresh_queued = true;
+    }
+    
+    let app = app.clone();
+    std::thread::spawn(move || {
+        let tracker = app.state::<BrewfileTracker>();
+        let _running = tracker.1.lock().unwrap_or_else(|e| e.into_inner());
+        // 拿到执行权后才清除标记，等待期间的请求都由这一次检查覆盖
+        if let Ok(mut state) = tracker.0.lock() {
+            state.refresh_queued = false;
+        }
+        refresh_locked(&app);
+    });
+}
+
+fn refresh_locked(app: &AppHandle) -> Vec<BrewfileDrift> {
+    let tracker = app.state::<BrewfileTracker>();
+    let paths = match tracker.0.lock() {
+        Ok(state) => state.paths.clone(),
+        Err(_) => return vec![],
+    };
+    
+    let mut results = Vec::new();
+    for path in paths {
+        let drift = compute_drift(&path);
+        let changed = {
+            let Ok(mut state) = tracker.0.lock() else {
+                continue;
+            };
+            // 文件被删除时也要记录，否则监视线程会一直认为它发生了变化
+            state.modified.insert(path.clone(), modified_time(&path));
+            let previous = state.drift.insert(path.clone(), drift.clone());
+            previous.map(|p| p.signature()) != Some(drift.signature())
+        };
+        if changed && drift.has_drift() {
+            let _ = app.emit("brewfile-drift", &drift);
+        }
+        results.push(drift);
+    }
+    
+    results
+}
+
+/// 加载已保存的跟踪列表，并在后台监视文件修改
+pub(crate) fn start_watching(app: &AppHandle) {
+    let paths: Vec<String> = storage::read_json(app, TRACKED_FILE).unwrap_or_default();
+    if let Ok(mut state) = app.state::<BrewfileTracker>().0.lock() {
+        state.paths = paths;
+    }
+    
+    let app = app.clone();
+    std::thread::spawn(move || {
+        refresh_drift(&app);
+        loop {
+            std::thread::sleep(WATCH_INTERVAL);
+            let changed = match app.state::<BrewfileTracker>().0.lock() {
+                Ok(state) => state
+                    .paths
+                    .iter()
+                    .any(|p| state.modified.get(p) != Some(&modified_time(p))),
+                Err(_) => false,
+            };
+            if changed {
+                refresh_drift(&app);
+            }
+        }
+    });
+}
+
+fn save_tracked(app: &AppHandle, tracker: &BrewfileTracker) -> Result<(), String> {
+    let paths = tracker.0.lock().map_err(|e| e.to_string())?.paths.clone();
+    storage::write_json(app, TRACKED_FILE, &paths)
+}
+
+/// 开始跟踪一个 Brewfile
+#[tauri::command]
+pub async fn track_brewfile(
+    path: String,
+    app: AppHandle,
+    tracker: State<'_, BrewfileTracker>,
+) -> Result<BrewfileDrift, String> {
+    {
+        let mut state = tracker.0.lock().map_err(|e| e.to_string())?;
+        if !state.paths.contains(&path) {
+            state.paths.push(path.clone());
+        }
+    }
+    save_tracked(&app, &tracker)?;
+    
+    refresh_drift(&app)
+        .into_iter()
+        .find(|d| d.path == path)
+        .ok_or_else(|| format!("Failed to check Brewfile: {}", path))
+}
+
+/// 停止跟踪一个 Brewfile
+#[tauri::command]
+pub async fn untrack_brewfile(
+    path: String,
+    app: AppHandle,
+    tracker: State<'_, BrewfileTracker>,
+) -> Result<(), String> {
+    {
+        let mut state = tracker.0.lock().map_err(|e| e.to_string())?;
+        state.paths.retain(|p| p != &path);
+        state.modified.remove(&path);
+        state.drift.remove(&path);
+    }
+    save_tracked(&app, &tracker)
+}
+
+/// 列出被跟踪的 Brewfile
+#[tauri::command]
+pub async fn list_tracked_brewfiles(
+    tracker: State<'_, BrewfileTracker>,
+) -> Result<Vec<String>, String> {
+    Ok(tracker.0.lock().map_err(|e| e.to_string())?.paths.clone())
+}
+
+/// 获取被跟踪 Brewfile 的偏离；refresh 为 true 或尚未检查过时重新计算
+#[tauri::command]
+pub async fn get_brewfile_drift(
+    refresh: Option<bool>,
+    app: AppHandle,
+    tracker: State<'_, BrewfileTracker>,
+) -> Result<Vec<BrewfileDrift>, String> {
+    let cached: Option<Vec<BrewfileDrift>> = {
+        let state = tracker.0.lock().map_err(|e| e.to_string())?;
+        state
+            .paths
+            .iter()
+            .map(|p| state.drift.get(p).cloned())
+            .collect()
+    };
+    
+    match cached {
+        Some(drift) if !refresh.unwrap_or(false) => Ok(drift),
+        _ => Ok(refresh_drift(&app)),
+    }
+}
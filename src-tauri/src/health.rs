@@ -0,0 +1,317 @@
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+use tauri::Manager;
+
+use crate::homebrew::{
+    execute_brew_command_with_progress, get_brew_cache, get_brew_prefix, get_directory_size,
+    CommandOutput,
+};
+use crate::operations;
+
+/// 缓存超过该大小时给出提示（5 GB）
+const CACHE_SIZE_LIMIT: u64 = 5 * 1024 * 1024 * 1024;
+/// 需要可写的前缀子目录
+const PREFIX_DIRS: [&str; 10] = [
+    "bin", "etc", "include", "lib", "opt", "sbin", "share", "var", "Cellar", "Caskroom",
+];
+
+/// 一键修复的方式
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum HealthFix {
+    /// 执行 brew 命令
+    BrewCommand { args: Vec<String> },
+    /// 删除文件（仅限前缀内的残留符号链接或锁文件）
+    RemoveFiles { paths: Vec<String> },
+}
+
+/// 一项健康检查发现的问题
+#[derive(Debug, Serialize, Deserialize)]
+pub struct HealthIssue {
+    /// 稳定的标识，用于 apply_health_fix
+    pub id: String,
+    /// broken_symlinks / missing_receipt / stale_locks / unwritable_dirs / shadowed_binaries / oversized_cache
+    pub kind: String,
+    pub title: String,
+    pub detail: String,
+    pub paths: Vec<String>,
+    pub fix: Option<HealthFix>,
+}
+
+fn read_dir_paths(path: &Path) -> Vec<PathBuf> {
+    std::fs::read_dir(path)
+        .map(|entries| entries.filter_map(|e| e.ok()).map(|e| e.path()).collect())
+        .unwrap_or_default()
+}
+
+fn path_strings(paths: &[PathBuf]) -> Vec<String> {
+    paths
+        .iter()
+        .map(|p| p.to_string_lossy().to_string())
+        .collect()
+}
+
+/// bin/ 中指向不存在目标的符号链接
+fn check_broken_symlinks(prefix: &Path) -> Option<HealthIssue> {
+    let mut broken: Vec<PathBuf> = Vec::new();
+    for dir in ["bin", "sbin"] {
+        for path in read_dir_paths(&prefix.join(dir)) {
+            let is_symlink = std::fs::symlink_metadata(&path)
+                .map(|m| m.file_type().is_symlink())
+                .unwrap_or(false);
+            if is_symlink && std::fs::metadata(&path).is_err() {
+                broken.push(path);
+            }
+        }
+    }
+    
+    if broken.is_empty() {
+        return None;
+    }
+    
+    Some(HealthIssue {
+        id: "broken_symlinks".to_string(),
+        kind: "broken_symlinks".to_string(),
+        title: format!("{} broken symlinks in the prefix", broken.len()),
+        detail: "These links point to kegs that no longer exist.".to_string(),
+        paths: path_strings(&broken),
+        fix: Some(HealthFix::RemoveFiles {
+            paths: path_strings(&broken),
+        }),
+    })
+}
+
+/// Cellar 中没有 INSTALL_RECEIPT.json 的 keg
+fn check_missing_receipts(prefix: &Path) -> Vec<HealthIssue> {
+    let mut issues = Vec::new();
+    
+    for formula_dir in read_dir_paths(&prefix.join("Cellar")) {
+        let Some(name) = formula_dir
+            .file_name()
+            .map(|n| n.to_string_lossy().to_string())
+        else {
+            continue;
+        };
+        let missing: Vec<PathBuf> = read_dir_paths(&formula_dir)
+            .into_iter()
+            .filter(|keg| keg.is_dir() && !keg.join("INSTALL_RECEIPT.json").exists())
+            .collect();
+        if missing.is_empty() {
+            continue;
+        }
+        issues.push(HealthIssue {
+            id: format!("missing_receipt:{}", name),
+            kind: "missing_receipt".to_string(),
+            title: format!("{} has kegs without an install receipt", name),
+            detail: "The keg was not installed completely; reinstalling restores the receipt."
+                .to_string(),
+            paths: path_strings(&missing),
+            fix: Some(HealthFix::BrewCommand {
+                args: vec!["reinstall".to_string(), name],
+            }),
+        });
+    }
+    
+    issues
+}
+
+/// 锁文件当前是否没有进程持有：Homebrew 用 flock 加锁，能立即拿到锁说明是残留文件
+fn lock_is_free(path: &Path) -> bool {
+    std::fs::File::open(path).is_ok_and(|file| file.try_lock().is_ok())
+}
+
+/// var/homebrew/locks 下没有被任何进程持有的锁文件
+fn check_stale_locks(prefix: &Path) -> Option<HealthIssue> {
+    let stale: Vec<PathBuf> = read_dir_paths(&prefix.join("var/homebrew/locks"))
+        .into_iter()
+        .filter(|path| path.is_file() && lock_is_free(path))
+        .collect();
+    
+    if stale.is_empty() {
+        return None;
+    }
+    
+    Some(HealthIssue {
+        id: "stale_locks".to_string(),
+        kind: "stale_locks".to_string(),
+        title: format!("{} leftover lock files", stale.len()),
+        detail: "No brew process holds these locks; they are harmless leftovers.".to_string(),
+        paths: path_strings(&stale),
+        fix: Some(HealthFix::RemoveFiles {
+            paths: path_strings(&stale),
+        }),
+    })
+}
+
+/// 通过创建临时文件检测目录是否可写
+fn is_writable(dir: &Path) -> bool {
+    let probe = dir.join(".homebrew-manager-write-test");
+    match std::fs::File::create(&probe) {
+        Ok(_) => {
+            let _ = std::fs::remove_file(&probe);
+            true
+        }
+        Err(_) => false,
+    }
+}
+
+/// 前缀中当前用户无法写入的目录
+fn check_unwritable_dirs(prefix: &Path) -> Option<HealthIssue> {
+    let unwritable: Vec<PathBuf> = PREFIX_DIRS
+        .iter()
+        .map(|d| prefix.join(d))
+        .filter(|d| d.is_dir() && !is_writable(d))
+        .collect();
+    
+    if unwritable.is_empty() {
+        return None;
+    }
+    
+    let joined = path_strings(&unwritable).join(" ");
+    Some(HealthIssue {
+        id: "unwritable_dirs".to_string(),
+        kind: "unwritable_dirs".to_string(),
+        title: format!("{} prefix directories are not writable", unwritable.len()),
+        detail: format!(
+            "Fix the ownership in a terminal:\n  sudo chown -R $(whoami) {}",
+            joined
+        ),
+        paths: path_strings(&unwritable),
+        // 需要 sudo，无法在应用内修复
+        fix: None,
+    })
+}
+
+/// 终端中使用的 PATH
+///
+/// 从 Finder 启动的应用继承的是 launchd 的精简 PATH，通常不含 Homebrew，因此读取登录 shell 的 PATH，
+/// 失败时使用系统默认目录加上 Homebrew 目录
+fn login_search_path(brew_bin: &Path) -> Vec<PathBuf> {
+    let shell = std::env::var("SHELL").unwrap_or_else(|_| "/bin/zsh".to_string());
+    let login_path = std::process::Command::new(&shell)
+        .args(["-l", "-c", "printf %s \"$PATH\""])
+        .output()
+        .ok()
+        .filter(|o| o.status.success())
+        .map(|o| String::from_utf8_lossy(&o.stdout).trim().to_string())
+        .filter(|p| !p.is_empty());
+    if let Some(path) = login_path {
+        return std::env::split_paths(&path).collect();
+    }
+    
+    let mut dirs = vec![brew_bin.to_path_buf()];
+    for dir in ["/usr/local/bin", "/usr/bin", "/bin", "/usr/sbin", "/sbin"] {
+        if !dirs.iter().any(|d| d == Path::new(dir)) {
+            dirs.push(PathBuf::from(dir));
+        }
+    }
+    dirs
+}
+
+/// PATH 中排在 Homebrew 之前、与 Homebrew 同名的可执行文件
+fn check_shadowed_binaries(prefix: &Path) -> Option<HealthIssue> {
+    let brew_bin = prefix.join("bin");
+    let search_path = login_search_path(&brew_bin);
+    let brew_index = search_path.iter().position(|p| p == &brew_bin)?;
+    
+    let mut shadowed: Vec<PathBuf> = Vec::new();
+    for binary in read_dir_paths(&brew_bin) {
+        let Some(file_name) = binary.file_name() else {
+            continue;
+        };
+        if let Some(earlier) = search_path[..brew_index]
+            .iter()
+            .map(|dir| dir.join(file_name))
+            .find(|candidate| candidate.is_file())
+        {
+            shadowed.push(earlier);
+        }
+    }
+    
+    if shadowed.is_empty() {
+        return None;
+    }
+    
+    Some(HealthIssue {
+        id: "shadowed_binaries".to_string(),
+        kind: "shadowed_binaries".to_string(),
+        title: format!("{} binaries shadow Homebrew ones in PATH", shadowed.len()),
+        detail: format!(
+            "These executables come earlier in PATH than {} and take precedence.",
+            brew_bin.to_string_lossy()
+        ),
+        paths: path_strings(&shadowed),
+        fix: None,
+    })
+}
+
+/// 下载缓存是否过大
+fn check_cache_size() -> Option<HealthIssue> {
+    let cache = get_brew_cache().ok().filter(|c| !c.is_empty())?;
+    let size = get_directory_size(&cache).ok()?;
+    
+    if size <= CACHE_SIZE_LIMIT {
+        return None;
+    }
+    
+    Some(HealthIssue {
+        id: "oversized_cache".to_string(),
+        kind: "oversized_cache".to_string(),
+        title: format!(
+            "Download cache uses {:.1} GB",
+            size as f64 / 1024f64.powi(3)
+        ),
+        detail: "Old downloads can be removed with brew cleanup.".to_string(),
+        paths: vec![cache],
+        fix: Some(HealthFix::BrewCommand {
+            args: vec!["cleanup".to_string(), "--prune=all".to_string()],
+        }),
+    })
+}
+
+fn collect_health_issues() -> Result<Vec<HealthIssue>, String> {
+    let prefix = PathBuf::from(get_brew_prefix()?);
+    
+    let mut issues = Vec::new();
+    issues.extend(check_broken_symlinks(&prefix));
+    issues.extend(check_missing_receipts(&prefix));
+    issues.extend(check_stale_locks(&prefix));
+    issues.extend(check_unwritable_dirs(&prefix));
+    issues.extend(check_shadowed_binaries(&prefix));
+    issues.extend(check_cache_size());
+    
+    Ok(issues)
+}
+
+/// 直接检查 Homebrew 前缀，发现 brew doctor 不覆盖的问题
+#[tauri::command]
+pub async fn run_health_checks() -> Result<Vec<HealthIssue>, String> {
+    collect_health_issues()
+}
+
+/// 执行某项问题的一键修复。会重新检查以确认问题仍然存在，修复经由操作队列执行
+#[tauri::command]
+pub async fn apply_health_fix(id: String, window: tauri::Window) -> Result<CommandOutput, String> {
+    let issue = collect_health_issues()?
+        .into_iter()
+        .find(|i| i.id == id)
+        .ok_or_else(|| format!("Issue no longer present: {}", id))?;
+    let fix = issue
+        .fix
+        .ok_or_else(|| format!("No automatic fix available for {}", id))?;
+    
+    match fix {
+        HealthFix::BrewCommand { args } => {
+            execute_brew_command_with_progress(&args, &window, &id, "apply_health_fix").await
+        }
+        HealthFix::RemoveFiles { paths } => Ok(operations::remove_paths(
+            window.app_handle(),
+            "apply_health_fix",
+            &id,
+            paths,
+            |path| {
+                std::fs::remove_file(path).map_err(|e| format!("Failed to remove {}: {}", path, e))
+            },
+        )),
+    }
+}
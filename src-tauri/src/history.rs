@@ -0,0 +1,242 @@
+use serde::{Deserialize, Serialize};
+use std::fs::OpenOptions;
+use std::io::Write;
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::{SystemTime, UNIX_EPOCH};
+use tauri::AppHandle;
+
+use crate::homebrew::{execute_brew_command, CommandOutput};
+use crate::storage;
+
+const HISTORY_FILE: &str = "history.jsonl";
+const DEFAULT_PAGE_SIZE: usize = 50;
+
+static OPERATION_COUNTER: AtomicU64 = AtomicU64::new(0);
+
+/// 一次操作的完整记录
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct HistoryEntry {
+    pub id: String,
+    /// 触发操作的用户动作，如 `install_package`、`apply_brewfile`
+    pub action: String,
+    pub package: String,
+    /// 完整命令行
+    pub argv: Vec<String>,
+    /// 开始/结束时间（Unix 毫秒）
+    pub started_at: u64,
+    pub finished_at: u64,
+    pub success: bool,
+    pub exit_code: i32,
+    /// 操作前后已安装的版本；不针对单个包的操作为 None
+    pub versions_before: Option<Vec<String>>,
+    pub versions_after: Option<Vec<String>>,
+    pub stdout: String,
+    pub stderr: String,
+}
+
+/// 历史记录摘要（不含完整输出）
+#[derive(Debug, Serialize, Deserialize)]
+pub struct HistorySummary {
+    pub id: String,
+    pub action: String,
+    pub package: String,
+    pub argv: Vec<String>,
+    pub started_at: u64,
+    pub finished_at: u64,
+    pub success: bool,
+    pub exit_code: i32,
+    pub versions_before: Option<Vec<String>>,
+    pub versions_after: Option<Vec<String>>,
+}
+
+impl From<HistoryEntry> for HistorySummary {
+    fn from(entry: HistoryEntry) -> Self {
+        HistorySummary {
+            id: entry.id,
+            action: entry.action,
+            package: entry.package,
+            argv: entry.argv,
+            started_at: entry.started_at,
+            finished_at: entry.finished_at,
+            success: entry.success,
+            exit_code: entry.exit_code,
+            versions_before: entry.versions_before,
+            versions_after: entry.versions_after,
+        }
+    }
+}
+
+/// 历史查询条件
+#[derive(Debug, Default, Serialize, Deserialize)]
+#[serde(default, rename_all = "camelCase")]
+pub struct HistoryFilter {
+    pub package: Option<String>,
+    /// brew 子命令，如 `install`、`upgrade`
+    pub operation: Option<String>,
+    pub action: Option<String>,
+    pub success: Option<bool>,
+    /// 时间范围（Unix 毫秒）
+    pub since: Option<u64>,
+    pub until: Option<u64>,
+}
+
+impl HistoryFilter {
+    pub(crate) fn matches(&self, entry: &HistoryEntry) -> bool {
+        self.package.as_ref().is_none_or(|p| &entry.package == p)
+            && self
+                .operation
+                .as_ref()
+                .is_none_or(|op| entry.argv.get(1) == Some(op))
+            && self.action.as_ref().is_none_or(|a| &entry.action == a)
+            && self.success.is_none_or(|s| entry.success == s)
+            && self.since.is_none_or(|t| entry.started_at >= t)
+            && self.until.is_none_or(|t| entry.started_at <= t)
+    }
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct HistoryPageRequest {
+    pub offset: usize,
+    pub limit: Option<usize>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct HistoryPage {
+    pub entries: Vec<HistorySummary>,
+    pub total: usize,
+}
+
+pub(crate) fn now_millis() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_millis() as u64)
+        .unwrap_or(0)
+}
+
+/// 生成操作 ID（时间戳 + 进程内计数）
+pub(crate) fn new_operation_id() -> String {
+    let counter = OPERATION_COUNTER.fetch_add(1, Ordering::Relaxed);
+    format!("{}-{}", now_millis(), counter)
+}
+
+/// 包当前已安装的版本（brew list --versions）
+pub(crate) fn installed_versions(name: &str, is_cask: bool) -> Option<Vec<String>> {
+    let mut args = vec!["list", "--versions"];
+    if is_cask {
+        args.push("--cask");
+    }
+    args.push(name);
+    let output = execute_brew_command(&args).ok()?;
+    
+    // 未安装时 brew 以非零状态退出
+    if !output.success {
+        return Some(vec![]);
+    }
+    
+    Some(
+        output
+            .stdout
+            .split_whitespace()
+            .skip(1)
+            .map(|v| v.to_string())
+            .collect(),
+    )
+}
+
+/// 历史记录文件路径（JSON Lines，每行一条记录）
+pub(crate) fn history_path(app: &AppHandle) -> Result<PathBuf, String> {
+    Ok(storage::data_dir(app)?.join(HISTORY_FILE))
+}
+
+/// 追加一条历史记录
+pub(crate) fn append(app: &AppHandle, entry: &HistoryEntry) -> Result<(), String> {
+    let path = history_path(app)?;
+    let line = serde_json::to_string(entry)
+        .map_err(|e| format!("Failed to serialize history entry: {}", e))?;
+    
+    let mut file = OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(&path)
+        .map_err(|e| format!("Failed to open history: {}", e))?;
+    writeln!(file, "{}", line).map_err(|e| format!("Failed to write history: {}", e))
+}
+
+/// 记录不经过 brew 的操作（如 mas 安装、删除残留文件）
+pub(crate) fn record_external(
+    app: &AppHandle,
+    action: &str,
+    package: &str,
+    argv: Vec<String>,
+    started_at: u64,
+    output: &CommandOutput,
+) {
+    let entry = HistoryEntry {
+        id: new_operation_id(),
+        action: action.to_string(),
+        package: package.to_string(),
+        argv,
+        started_at,
+        finished_at: now_millis(),
+        success: output.success,
+        exit_code: output.exit_code,
+        versions_before: None,
+        versions_after: None,
+        stdout: output.stdout.clone(),
+        stderr: output.stderr.clone(),
+    };
+    let _ = append(app, &entry);
+}
+
+/// 读取全部历史记录（按时间顺序），跳过损坏的行
+pub(crate) fn read_all(app: &AppHandle) -> Result<Vec<HistoryEntry>, String> {
+    let path = history_path(app)?;
+    let content = match std::fs::read_to_string(&path) {
+        Ok(content) => content,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(vec![]),
+        Err(e) => return Err(format!("Failed to read history: {}", e)),
+    };
+    
+    Ok(content
+        .lines()
+        .filter_map(|line| serde_json::from_str(line).ok())
+        .collect())
+}
+
+/// 分页查询历史记录（最新的在前）
+#[tauri::command]
+pub async fn query_history(
+    filter: Option<HistoryFilter>,
+    page: Option<HistoryPageRequest>,
+    app: AppHandle,
+) -> Result<HistoryPage, String> {
+    let filter = filter.unwrap_or_default();
+    let page = page.unwrap_or_default();
+    
+    let mut matching: Vec<HistoryEntry> = read_all(&app)?
+        .into_iter()
+        .filter(|e| filter.matches(e))
+        .collect();
+    matching.reverse();
+    
+    let total = matching.len();
+    let entries = matching
+        .into_iter()
+        .skip(page.offset)
+        .take(page.limit.unwrap_or(DEFAULT_PAGE_SIZE))
+        .map(HistorySummary::from)
+        .collect();
+    
+    Ok(HistoryPage { entries, total })
+}
+
+/// 获取单条历史记录（含完整输出）
+#[tauri::command]
+pub async fn get_history_entry(id: String, app: AppHandle) -> Result<HistoryEntry, String> {
+    read_all(&app)?
+        .into_iter()
+        .find(|e| e.id == id)
+        .ok_or_else(|| format!("History entry not found: {}", id))
+}
@@ -1,6 +1,8 @@
 use serde::{Deserialize, Serialize};
 use std::process::Command;
-use tauri::Emitter;
+use tauri::{Emitter, Manager};
+
+use crate::history;
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct Package {
@@ -11,6 +13,11 @@ pub struct Package {
     pub installed: bool,
     pub outdated: bool,
     pub description: Option<String>,
+    /// formula 的 keg 是否已链接到前缀中；cask 或未知时为 None
+    pub linked: Option<bool>,
+    /// formula 是否声明了后台服务（brew services）
+    #[serde(rename = "hasService")]
+    pub has_service: bool,
 }
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -66,8 +73,38 @@ fn get_brew_path() -> String {
     "brew".to_string()
 }
 
+impl CommandOutput {
+    /// 把后续步骤的输出合并进来（用于多步骤操作）
+    pub(crate) fn append(&mut self, other: CommandOutput) {
+        self.success = self.success && other.success;
+        self.stdout.push_str(&other.stdout);
+        self.stderr.push_str(&other.stderr);
+        self.exit_code = other.exit_code;
+    }
+}
+
+/// 获取 Homebrew 安装前缀（brew --prefix）
+pub(crate) fn get_brew_prefix() -> Result<String, String> {
+    let output = Command::new(get_brew_path())
+        .args(["--prefix"])
+        .output()
+        .map_err(|e| format!("Failed to get brew prefix: {}", e))?;
+    
+    Ok(String::from_utf8_lossy(&output.stdout).trim().to_string())
+}
+
+/// 获取 Homebrew 下载缓存目录（brew --cache）
+pub(crate) fn get_brew_cache() -> Result<String, String> {
+    let output = Command::new(get_brew_path())
+        .args(["--cache"])
+        .output()
+        .map_err(|e| format!("Failed to get cache path: {}", e))?;
+    
+    Ok(String::from_utf8_lossy(&output.stdout).trim().to_string())
+}
+
 /// 执行 brew 命令
-fn execute_brew_command(args: &[&str]) -> Result<CommandOutput, String> {
+pub(crate) fn execute_brew_command(args: &[&str]) -> Result<CommandOutput, String> {
     let brew_path = get_brew_path();
     
     let output = Command::new(&brew_path)
@@ -100,7 +137,18 @@ pub async fn check_homebrew() -> Result<bool, String> {
 
 /// 列出所有已安装的包
 #[tauri::command]
-pub async fn list_installed() -> Result<Vec<Package>, String> {
+pub async fn list_installed(app: tauri::AppHandle) -> Result<Vec<Package>, String> {
+    let packages = installed_packages()?;
+    crate::search_index::remember_installed(&app, &packages);
+    
+    // 安装列表刷新后，在后台重新检查被跟踪的 Brewfile
+    crate::drift::request_refresh(&app);
+    
+    Ok(packages)
+}
+
+/// 获取已安装的包，JSON 方式失败时回退到 brew list
+pub(crate) fn installed_packages() -> Result<Vec<Package>, String> {
     let mut packages = Vec::new();
     
     // 获取已安装的 formulas（带描述）
@@ -118,6 +166,8 @@ pub async fn list_installed() -> Result<Vec<Package>, String> {
             desc: Option<String>,
             installed: Vec<InstalledVersion>,
             outdated: bool,
+            linked_keg: Option<String>,
+            service: Option<serde_json::Value>,
         }
         
         #[derive(Debug, Deserialize)]
@@ -144,6 +194,8 @@ pub async fn list_installed() -> Result<Vec<Package>, String> {
                     installed: true,
                     outdated: f.outdated,
                     description: f.desc,
+                    linked: Some(f.linked_keg.is_some()),
+                    has_service: f.service.is_some(),
                 });
             }
             
@@ -155,6 +207,8 @@ pub async fn list_installed() -> Result<Vec<Package>, String> {
                     installed: true,
                     outdated: c.outdated,
                     description: c.desc,
+                    linked: None,
+                    has_service: false,
                 });
             }
         }
@@ -176,6 +230,8 @@ pub async fn list_installed() -> Result<Vec<Package>, String> {
                         installed: true,
                         outdated: false,
                         description: None,
+                        linked: None,
+                        has_service: false,
                     });
                 }
             }
@@ -195,6 +251,8 @@ pub async fn list_installed() -> Result<Vec<Package>, String> {
                         installed: true,
                         outdated: false,
                         description: None,
+                        linked: None,
+                        has_service: false,
                     });
                 }
             }
@@ -269,11 +327,19 @@ fn get_outdated_internal() -> Result<Vec<OutdatedPackage>, String> {
 
 /// 搜索包
 #[tauri::command]
-pub async fn search_packages(query: String) -> Result<Vec<Package>, String> {
+pub async fn search_packages(
+    query: String,
+    app: tauri::AppHandle,
+) -> Result<Vec<crate::search_index::SearchResult>, String> {
     if query.trim().is_empty() {
         return Ok(vec![]);
     }
     
+    // 优先使用缓存的 API 索引，没有时回退到 brew search
+    if let Some(results) = crate::search_index::search(&app, &query)? {
+        return Ok(results);
+    }
+    
     let output = execute_brew_command(&["search", &query])?;
     
     if !output.success {
@@ -314,10 +380,12 @@ pub async fn search_packages(query: String) -> Result<Vec<Package>, String> {
             installed: false,
             outdated: false,
             description: None,
+            linked: None,
+            has_service: false,
         });
     }
     
-    Ok(packages)
+    Ok(crate::search_index::rank_packages(&app, packages, &query))
 }
 
 /// 获取包详情
@@ -415,33 +483,177 @@ pub async fn get_package_info(name: String, is_cask: bool) -> Result<PackageInfo
     Err("Package not found".to_string())
 }
 
+/// 安装选项
+#[derive(Debug, Default, Serialize, Deserialize)]
+#[serde(default, rename_all = "camelCase")]
+pub struct InstallOptions {
+    /// 从源码编译（--build-from-source，仅 formula）
+    pub build_from_source: bool,
+    /// 安装 HEAD 版本（--HEAD，仅 formula）
+    pub head: bool,
+    /// 强制使用 bottle（--force-bottle，仅 formula）
+    pub force_bottle: bool,
+    /// 不安装依赖（--ignore-dependencies，仅 formula）
+    pub ignore_dependencies: bool,
+    /// 跳过 post_install 步骤（--skip-post-install，仅 formula）
+    pub skip_post_install: bool,
+    /// formula 声明的构建选项，如 `--with-openssl`
+    pub build_options: Vec<String>,
+    /// cask 应用的安装目录（--appdir）
+    pub appdir: Option<String>,
+    /// 不为 cask 应用添加隔离属性（--no-quarantine）
+    pub no_quarantine: bool,
+}
+
+impl InstallOptions {
+    fn to_flags(&self, is_cask: bool) -> Result<Vec<String>, String> {
+        let mut flags = Vec::new();
+        
+        if is_cask {
+            let formula_only = self.build_from_source
+                || self.head
+                || self.force_bottle
+                || self.ignore_dependencies
+                || self.skip_post_install
+                || !self.build_options.is_empty();
+            if formula_only {
+                return Err("Build options are only supported for formulae".to_string());
+            }
+            if let Some(appdir) = self.appdir.as_deref().filter(|d| !d.trim().is_empty()) {
+                flags.push(format!("--appdir={}", appdir));
+            }
+            if self.no_quarantine {
+                flags.push("--no-quarantine".to_string());
+            }
+            return Ok(flags);
+        }
+        
+        if self.appdir.is_some() || self.no_quarantine {
+            return Err("--appdir and --no-quarantine are only supported for casks".to_string());
+        }
+        if self.force_bottle && (self.build_from_source || self.head) {
+            return Err("--force-bottle cannot be combined with --build-from-source or --HEAD".to_string());
+        }
+        
+        if self.build_from_source {
+            flags.push("--build-from-source".to_string());
+        }
+        if self.head {
+            flags.push("--HEAD".to_string());
+        }
+        if self.force_bottle {
+            flags.push("--force-bottle".to_string());
+        }
+        if self.ignore_dependencies {
+            flags.push("--ignore-dependencies".to_string());
+        }
+        if self.skip_post_install {
+            flags.push("--skip-post-install".to_string());
+        }
+        flags.extend(self.build_options.iter().cloned());
+        
+        Ok(flags)
+    }
+}
+
+/// 获取 formula 声明的构建选项（如 `--with-openssl`）
+fn get_formula_options(name: &str) -> Result<Vec<String>, String> {
+    let output = execute_brew_command(&["info", "--json=v2", name])?;
+    
+    if !output.success {
+        return Err(format!("Failed to get package info: {}", output.stderr));
+    }
+    
+    #[derive(Debug, Deserialize)]
+    struct InfoJson {
+        formulae: Vec<FormulaOptions>,
+    }
+    
+    #[derive(Debug, Deserialize)]
+    struct FormulaOptions {
+        #[serde(default)]
+        options: Vec<FormulaOption>,
+    }
+    
+    #[derive(Debug, Deserialize)]
+    struct FormulaOption {
+        option: String,
+    }
+    
+    let json: InfoJson = serde_json::from_str(&output.stdout)
+        .map_err(|e| format!("Failed to parse info JSON: {}", e))?;
+    
+    Ok(json
+        .formulae
+        .into_iter()
+        .flat_map(|f| f.options)
+        .map(|o| o.option)
+        .collect())
+}
+
 /// 安装包（带实时输出）
 #[tauri::command]
 pub async fn install_package(
     name: String,
     is_cask: bool,
+    options: Option<InstallOptions>,
     window: tauri::Window,
 ) -> Result<CommandOutput, String> {
-    let args: Vec<String> = if is_cask {
-        vec!["install".to_string(), "--cask".to_string(), name.clone()]
-    } else {
-        vec!["install".to_string(), name.clone()]
-    };
+    let options = options.unwrap_or_default();
+    let flags = options.to_flags(is_cask)?;
     
-    execute_brew_command_with_progress(&args, &window, &name).await
+    if !options.build_options.is_empty() {
+        let declared = get_formula_options(&name)?;
+        if let Some(unknown) = options.build_options.iter().find(|o| !declared.contains(o)) {
+            return Err(format!("Unknown option for {}: {}", name, unknown));
+        }
+    }
+    
+    let mut args = package_args("install", &name, is_cask);
+    args.extend(flags);
+    
+    execute_brew_command_with_progress(&args, &window, &name, "install_package").await
+}
+
+/// 执行 brew 命令并实时发送进度，同时记录到操作历史
+pub(crate) async fn execute_brew_command_with_progress(
+    args: &[String],
+    window: &tauri::Window,
+    package_name: &str,
+    action: &str,
+) -> Result<CommandOutput, String> {
+    let queue = crate::operations::enter_queue();
+    if crate::snapshots::is_mutating(args) {
+        crate::snapshots::auto_snapshot(window.app_handle(), action, package_name);
+    }
+    execute_brew_command_queued(&queue, args, window, package_name, action).await
 }
 
-/// 执行 brew 命令并实时发送进度
-async fn execute_brew_command_with_progress(
+/// 在已进入的操作队列中执行 brew 命令；多步操作全程持有同一个 guard，中途不会插入其他操作，
+/// 并由调用方在开始时拍一次快照
+pub(crate) async fn execute_brew_command_queued(
+    _queue: &crate::operations::QueueGuard,
     args: &[String],
     window: &tauri::Window,
     package_name: &str,
+    action: &str,
 ) -> Result<CommandOutput, String> {
     use std::io::{BufRead, BufReader};
     use std::process::Stdio;
     
     let brew_path = get_brew_path();
     
+    let operation_id = history::new_operation_id();
+    // 只有针对单个包的操作才记录前后版本
+    let is_cask = args.iter().any(|a| a == "--cask");
+    let targets_package = args.iter().skip(1).any(|a| a == package_name);
+    let versions_before = if targets_package {
+        history::installed_versions(package_name, is_cask)
+    } else {
+        None
+    };
+    let started_at = history::now_millis();
+    
     let mut child = Command::new(&brew_path)
         .args(args)
         .stdout(Stdio::piped())
@@ -458,45 +670,121 @@ async fn execute_brew_command_with_progress(
     // 读取 stdout
     if let Some(stdout) = stdout {
         let reader = BufReader::new(stdout);
-        for line in reader.lines() {
-            if let Ok(line) = line {
-                all_stdout.push_str(&line);
-                all_stdout.push('\n');
-                // 发送进度事件到前端
-                let _ = window.emit("install-progress", serde_json::json!({
-                    "package": package_name,
-                    "line": line,
-                    "type": "stdout"
-                }));
-            }
+        for line in reader.lines().map_while(Result::ok) {
+            all_stdout.push_str(&line);
+            all_stdout.push('\n');
+            // 发送进度事件到前端
+            let _ = window.emit("install-progress", serde_json::json!({
+                "package": package_name,
+                "line": line,
+                "type": "stdout",
+                "operationId": operation_id
+            }));
         }
     }
     
     // 读取 stderr（brew 的进度信息通常在 stderr）
     if let Some(stderr) = stderr {
         let reader = BufReader::new(stderr);
-        for line in reader.lines() {
-            if let Ok(line) = line {
-                all_stderr.push_str(&line);
-                all_stderr.push('\n');
-                // 发送进度事件到前端
-                let _ = window.emit("install-progress", serde_json::json!({
-                    "package": package_name,
-                    "line": line,
-                    "type": "stderr"
-                }));
-            }
+        for line in reader.lines().map_while(Result::ok) {
+            all_stderr.push_str(&line);
+            all_stderr.push('\n');
+            // 发送进度事件到前端
+            let _ = window.emit("install-progress", serde_json::json!({
+                "package": package_name,
+                "line": line,
+                "type": "stderr",
+                "operationId": operation_id
+            }));
         }
     }
     
     let status = child.wait().map_err(|e| format!("Failed to wait for command: {}", e))?;
     
-    Ok(CommandOutput {
+    let output = CommandOutput {
         success: status.success(),
         stdout: all_stdout,
         stderr: all_stderr,
         exit_code: status.code().unwrap_or(-1),
-    })
+    };
+    
+    let entry = history::HistoryEntry {
+        id: operation_id,
+        action: action.to_string(),
+        package: package_name.to_string(),
+        argv: std::iter::once(brew_path).chain(args.iter().cloned()).collect(),
+        started_at,
+        finished_at: history::now_millis(),
+        success: output.success,
+        exit_code: output.exit_code,
+        versions_after: if targets_package {
+            history::installed_versions(package_name, is_cask)
+        } else {
+            None
+        },
+        versions_before,
+        stdout: output.stdout.clone(),
+        stderr: output.stderr.clone(),
+    };
+    // 历史写入失败不影响操作结果
+    let _ = history::append(window.app_handle(), &entry);
+    
+    // 安装状态变化后，包大小和已安装列表的缓存失效；链接状态变化只影响已安装列表
+    match args.first().map(|a| a.as_str()) {
+        Some("install" | "uninstall" | "reinstall" | "upgrade" | "cleanup") => {
+            let package = targets_package.then_some(package_name);
+            crate::disk::invalidate_sizes(window.app_handle(), package);
+            crate::search_index::forget_installed(window.app_handle());
+        }
+        Some("link" | "unlink") => crate::search_index::forget_installed(window.app_handle()),
+        _ => {}
+    }
+    
+    Ok(output)
+}
+
+/// 构造 `brew <subcommand> [--cask] <name>` 形式的参数
+pub(crate) fn package_args(subcommand: &str, name: &str, is_cask: bool) -> Vec<String> {
+    let mut args = vec![subcommand.to_string()];
+    if is_cask {
+        args.push("--cask".to_string());
+    }
+    args.push(name.to_string());
+    args
+}
+
+/// 卸载选项
+#[derive(Debug, Default, Serialize, Deserialize)]
+#[serde(default, rename_all = "camelCase")]
+pub struct UninstallOptions {
+    /// 删除该包的所有已安装版本（--force）
+    pub force: bool,
+    /// 即使有其他包依赖它也卸载（--ignore-dependencies）
+    pub ignore_dependencies: bool,
+    /// 同时删除 cask 的全部关联文件（--zap，仅 cask）
+    pub zap: bool,
+}
+
+impl UninstallOptions {
+    fn to_flags(&self, is_cask: bool) -> Result<Vec<String>, String> {
+        let mut flags = Vec::new();
+        if self.force {
+            flags.push("--force".to_string());
+        }
+        if self.ignore_dependencies {
+            if is_cask {
+                return Err("--ignore-dependencies is only supported for formulae".to_string());
+            }
+            flags.push("--ignore-dependencies".to_string());
+        }
+        if self.zap {
+            if !is_cask {
+                return Err("--zap is only supported for casks".to_string());
+            }
+            flags.push("--zap".to_string());
+        }
+        Ok(flags)
+    }
 }
 
 /// 卸载包（带实时输出）
@@ -504,15 +792,61 @@ async fn execute_brew_command_with_progress(
 pub async fn uninstall_package(
     name: String,
     is_cask: bool,
+    options: Option<UninstallOptions>,
     window: tauri::Window,
 ) -> Result<CommandOutput, String> {
-    let args: Vec<String> = if is_cask {
-        vec!["uninstall".to_string(), "--cask".to_string(), name.clone()]
-    } else {
-        vec!["uninstall".to_string(), name.clone()]
-    };
+    let options = options.unwrap_or_default();
+    let mut args = package_args("uninstall", &name, is_cask);
+    args.extend(options.to_flags(is_cask)?);
+    
+    execute_brew_command_with_progress(&args, &window, &name, "uninstall_package").await
+}
+
+/// 重新安装包（带实时输出）
+#[tauri::command]
+pub async fn reinstall_package(
+    name: String,
+    is_cask: bool,
+    window: tauri::Window,
+) -> Result<CommandOutput, String> {
+    let args = package_args("reinstall", &name, is_cask);
+    execute_brew_command_with_progress(&args, &window, &name, "reinstall_package").await
+}
+
+/// 下载选项
+#[derive(Debug, Default, Serialize, Deserialize)]
+#[serde(default, rename_all = "camelCase")]
+pub struct FetchOptions {
+    /// 即使缓存中已存在也重新下载（--force）
+    pub force: bool,
+    /// 下载源码而不是 bottle（--build-from-source，仅 formula）
+    pub build_from_source: bool,
+}
+
+/// 仅下载 bottle/cask 到缓存而不安装（brew fetch），用于离线前预先准备
+#[tauri::command]
+pub async fn fetch_package(
+    name: String,
+    is_cask: bool,
+    options: Option<FetchOptions>,
+    window: tauri::Window,
+) -> Result<CommandOutput, String> {
+    let options = options.unwrap_or_default();
+    let mut args = vec!["fetch".to_string()];
+    if is_cask {
+        if options.build_from_source {
+            return Err("--build-from-source is only supported for formulae".to_string());
+        }
+        args.push("--cask".to_string());
+    } else if options.build_from_source {
+        args.push("--build-from-source".to_string());
+    }
+    if options.force {
+        args.push("--force".to_string());
+    }
+    args.push(name.clone());
     
-    execute_brew_command_with_progress(&args, &window, &name).await
+    execute_brew_command_with_progress(&args, &window, &name, "fetch_package").await
 }
 
 /// 更新包（带实时输出）
@@ -522,13 +856,8 @@ pub async fn upgrade_package(
     is_cask: bool,
     window: tauri::Window,
 ) -> Result<CommandOutput, String> {
-    let args: Vec<String> = if is_cask {
-        vec!["upgrade".to_string(), "--cask".to_string(), name.clone()]
-    } else {
-        vec!["upgrade".to_string(), name.clone()]
-    };
-    
-    execute_brew_command_with_progress(&args, &window, &name).await
+    let args = package_args("upgrade", &name, is_cask);
+    execute_brew_command_with_progress(&args, &window, &name, "upgrade_package").await
 }
 
 /// 获取过时的包
@@ -542,21 +871,21 @@ pub async fn get_outdated() -> Result<Vec<OutdatedPackage>, String> {
 #[tauri::command]
 pub async fn update_homebrew(window: tauri::Window) -> Result<CommandOutput, String> {
     let args = vec!["update".to_string()];
-    execute_brew_command_with_progress(&args, &window, "homebrew").await
+    execute_brew_command_with_progress(&args, &window, "homebrew", "update_homebrew").await
 }
 
 /// 清理缓存（brew cleanup）
 #[tauri::command]
 pub async fn cleanup_homebrew(window: tauri::Window) -> Result<CommandOutput, String> {
     let args = vec!["cleanup".to_string(), "--prune=all".to_string()];
-    execute_brew_command_with_progress(&args, &window, "cleanup").await
+    execute_brew_command_with_progress(&args, &window, "cleanup", "cleanup_homebrew").await
 }
 
 /// 批量更新所有过时的包
 #[tauri::command]
 pub async fn upgrade_all(window: tauri::Window) -> Result<CommandOutput, String> {
     let args = vec!["upgrade".to_string()];
-    execute_brew_command_with_progress(&args, &window, "all").await
+    execute_brew_command_with_progress(&args, &window, "all", "upgrade_all").await
 }
 
 /// 获取 Homebrew 信息（版本、缓存大小等）
@@ -577,12 +906,7 @@ pub async fn get_homebrew_info() -> Result<HomebrewInfo, String> {
         .to_string();
     
     // 获取缓存大小
-    let cache_output = Command::new(&brew_path)
-        .args(["--cache"])
-        .output()
-        .map_err(|e| format!("Failed to get cache path: {}", e))?;
-    
-    let cache_path = String::from_utf8_lossy(&cache_output.stdout).trim().to_string();
+    let cache_path = get_brew_cache()?;
     
     let cache_size = if !cache_path.is_empty() {
         get_directory_size(&cache_path).unwrap_or(0)
@@ -604,37 +928,26 @@ pub struct HomebrewInfo {
     pub cache_size: u64,
 }
 
-/// 获取目录大小（字节）
-fn get_directory_size(path: &str) -> Result<u64, String> {
-    let output = Command::new("du")
-        .args(["-sk", path])
-        .output()
-        .map_err(|e| format!("Failed to get directory size: {}", e))?;
-    
-    if output.status.success() {
-        let stdout = String::from_utf8_lossy(&output.stdout);
-        let size_kb: u64 = stdout
-            .split_whitespace()
-            .next()
-            .and_then(|s| s.parse().ok())
-            .unwrap_or(0);
-        Ok(size_kb * 1024) // 转换为字节
-    } else {
-        Ok(0)
-    }
+/// 获取目录实际占用的大小（字节）
+pub(crate) fn get_directory_size(path: &str) -> Result<u64, String> {
+    crate::disk::measure_path(std::path::Path::new(path), crate::disk::walker_threads())
+        .map(|size| size.allocated)
+        .map_err(|e| format!("Failed to get directory size: {}", e))
 }
 
 
 /// 锁定包版本（brew pin）
 #[tauri::command]
-pub async fn pin_package(name: String) -> Result<CommandOutput, String> {
-    execute_brew_command(&["pin", &name])
+pub async fn pin_package(name: String, window: tauri::Window) -> Result<CommandOutput, String> {
+    let args = vec!["pin".to_string(), name.clone()];
+    execute_brew_command_with_progress(&args, &window, &name, "pin_package").await
 }
 
 /// 解锁包版本（brew unpin）
 #[tauri::command]
-pub async fn unpin_package(name: String) -> Result<CommandOutput, String> {
-    execute_brew_command(&["unpin", &name])
+pub async fn unpin_package(name: String, window: tauri::Window) -> Result<CommandOutput, String> {
+    let args = vec!["unpin".to_string(), name.clone()];
+    execute_brew_command_with_progress(&args, &window, &name, "unpin_package").await
 }
 
 /// 获取已锁定的包列表
@@ -657,58 +970,16 @@ pub async fn get_pinned() -> Result<Vec<String>, String> {
 
 /// 获取包的安装大小
 #[tauri::command]
-pub async fn get_package_size(name: String, is_cask: bool) -> Result<u64, String> {
-    let brew_path = get_brew_path();
-    
-    // 获取包的安装路径
-    let prefix_output = Command::new(&brew_path)
-        .args(["--prefix"])
-        .output()
-        .map_err(|e| format!("Failed to get brew prefix: {}", e))?;
-    
-    let prefix = String::from_utf8_lossy(&prefix_output.stdout).trim().to_string();
-    
-    if is_cask {
-        // Cask 应用通常在 /Applications 或 ~/Applications
-        let app_paths = [
-            format!("/Applications/{}.app", name),
-            format!("/Applications/{}.app", capitalize_first(&name)),
-            format!("{}/Caskroom/{}", prefix, name),
-        ];
-        
-        for path in &app_paths {
-            if std::path::Path::new(path).exists() {
-                if let Ok(size) = get_directory_size(path) {
-                    if size > 0 {
-                        return Ok(size);
-                    }
-                }
-            }
-        }
-        
-        // 尝试从 Caskroom 获取
-        let caskroom_path = format!("{}/Caskroom/{}", prefix, name);
-        if let Ok(size) = get_directory_size(&caskroom_path) {
-            return Ok(size);
-        }
-    } else {
-        // Formula 在 Cellar 目录
-        let cellar_path = format!("{}/Cellar/{}", prefix, name);
-        if let Ok(size) = get_directory_size(&cellar_path) {
-            return Ok(size);
-        }
-    }
-    
-    Ok(0)
-}
-
-/// 首字母大写
-fn capitalize_first(s: &str) -> String {
-    let mut chars = s.chars();
-    match chars.next() {
-        None => String::new(),
-        Some(first) => first.to_uppercase().collect::<String>() + chars.as_str(),
-    }
+pub async fn get_package_size(
+    name: String,
+    is_cask: bool,
+    app: tauri::AppHandle,
+) -> Result<u64, String> {
+    let prefix = get_brew_prefix()?;
+    let size =
+        crate::disk::measure_package(&app, &prefix, &name, is_cask, crate::disk::walker_threads())?;
+    crate::disk::save_size_cache(&app);
+    Ok(size.allocated)
 }
 
 /// 获取包的依赖关系
@@ -1,34 +1,142 @@
+mod brewfile;
+mod cask_artifacts;
+mod cleanup;
+mod disk;
+mod disk_report;
+mod doctor;
+mod download_cache;
+mod drift;
+mod health;
+mod history;
 mod homebrew;
+mod links;
+mod log_search;
+mod operations;
+mod rollback;
+mod search_index;
+mod services;
+mod snapshots;
+mod storage;
+mod taps;
+mod versions;
 
+use brewfile::{apply_brewfile, export_brewfile, plan_brewfile};
+use cask_artifacts::get_cask_size_breakdown;
+use cleanup::{cleanup_selected, plan_cleanup};
+use disk::{get_all_package_sizes, SizeCache};
+use disk_report::get_disk_report;
+use doctor::{get_brew_config, run_doctor};
+use download_cache::{
+    delete_cache_entries, export_cache_bundle, import_cache_bundle, list_cache_entries,
+};
+use drift::{
+    get_brewfile_drift, list_tracked_brewfiles, track_brewfile, untrack_brewfile, BrewfileTracker,
+};
+use health::{apply_health_fix, run_health_checks};
+use history::{get_history_entry, query_history};
 use homebrew::{
-    check_homebrew, cleanup_homebrew, get_dependencies, get_homebrew_info, get_outdated,
-    get_package_info, get_package_size, get_pinned, install_package, list_installed, pin_package,
-    search_packages, uninstall_package, unpin_package, update_homebrew, upgrade_all,
-    upgrade_package,
+    check_homebrew, cleanup_homebrew, fetch_package, get_dependencies, get_homebrew_info,
+    get_outdated, get_package_info, get_package_size, get_pinned, install_package, list_installed,
+    pin_package, reinstall_package, search_packages, uninstall_package, unpin_package,
+    update_homebrew, upgrade_all, upgrade_package,
+};
+use links::{link_package, list_keg_only, preview_link, preview_unlink, unlink_package};
+use log_search::{search_history_logs, HistoryLogIndex};
+use rollback::{execute_rollback, plan_rollback};
+use search_index::SearchIndex;
+use services::{
+    list_services, restart_service, run_service, start_service, start_service_monitor,
+    stop_service, stop_service_monitor, stop_tail_service_log, tail_service_log, ServiceLogTails,
+    ServiceMonitor,
 };
+use snapshots::{create_snapshot, diff_snapshots, list_snapshots};
+use taps::{add_tap, list_taps, remove_tap, tap_info};
+use versions::{downgrade_package, list_restorable_versions, list_versions, switch_version};
 
 #[cfg_attr(mobile, tauri::mobile_entry_point)]
 pub fn run() {
     tauri::Builder::default()
         .plugin(tauri_plugin_shell::init())
+        .manage(ServiceLogTails::default())
+        .manage(ServiceMonitor::default())
+        .manage(BrewfileTracker::default())
+        .manage(HistoryLogIndex::default())
+        .manage(SizeCache::default())
+        .manage(SearchIndex::default())
+        .setup(|app| {
+            drift::start_watching(app.handle());
+            Ok(())
+        })
         .invoke_handler(tauri::generate_handler![
             check_homebrew,
             list_installed,
             search_packages,
             get_package_info,
             get_package_size,
+            get_all_package_sizes,
+            get_cask_size_breakdown,
+            get_disk_report,
             install_package,
             uninstall_package,
+            reinstall_package,
+            fetch_package,
             upgrade_package,
             upgrade_all,
             get_outdated,
             update_homebrew,
             cleanup_homebrew,
+            plan_cleanup,
+            cleanup_selected,
+            list_cache_entries,
+            delete_cache_entries,
+            export_cache_bundle,
+            import_cache_bundle,
             get_homebrew_info,
             pin_package,
             unpin_package,
             get_pinned,
             get_dependencies,
+            list_versions,
+            switch_version,
+            list_restorable_versions,
+            downgrade_package,
+            link_package,
+            unlink_package,
+            preview_link,
+            preview_unlink,
+            list_keg_only,
+            list_taps,
+            tap_info,
+            add_tap,
+            remove_tap,
+            list_services,
+            start_service,
+            stop_service,
+            restart_service,
+            run_service,
+            tail_service_log,
+            stop_tail_service_log,
+            start_service_monitor,
+            stop_service_monitor,
+            run_doctor,
+            get_brew_config,
+            run_health_checks,
+            apply_health_fix,
+            export_brewfile,
+            plan_brewfile,
+            apply_brewfile,
+            track_brewfile,
+            untrack_brewfile,
+            list_tracked_brewfiles,
+            get_brewfile_drift,
+            query_history,
+            get_history_entry,
+            search_history_logs,
+            create_snapshot,
+            list_snapshots,
+            diff_snapshots,
+            plan_rollback,
+            execute_rollback,
         ])
         .run(tauri::generate_context!())
         .expect("error while running tauri application");
@@ -0,0 +1,192 @@
+use serde::{Deserialize, Serialize};
+
+use crate::brewfile::installed_state;
+use crate::homebrew::{execute_brew_command, execute_brew_command_with_progress, CommandOutput};
+
+/// 链接选项
+#[derive(Debug, Default, Serialize, Deserialize)]
+#[serde(default, rename_all = "camelCase")]
+pub struct LinkOptions {
+    /// 删除前缀中冲突的文件后再链接（--overwrite）
+    pub overwrite: bool,
+    /// 允许链接 keg-only 的 formula（--force）
+    pub force: bool,
+}
+
+/// `brew link --dry-run` 的预览结果
+#[derive(Debug, Serialize, Deserialize)]
+pub struct LinkPreview {
+    pub name: String,
+    /// 将被创建的符号链接
+    pub would_link: Vec<String>,
+    /// 前缀中已存在、需要 --overwrite 才能替换的文件
+    pub conflicts: Vec<String>,
+}
+
+/// keg-only 的已安装 formula，以及使用它所需的环境变量
+#[derive(Debug, Serialize, Deserialize)]
+pub struct KegOnlyFormula {
+    pub name: String,
+    pub version: String,
+    pub reason: String,
+    /// 从 caveats 中提取的 `export ...` 语句（PATH、LDFLAGS、CPPFLAGS、PKG_CONFIG_PATH 等）
+    pub exports: Vec<String>,
+    pub linked: bool,
+}
+
+/// 解析 dry-run 输出中 `Would link:` / `Would remove:` 之后的路径列表
+fn parse_dry_run_paths(output: &str, header: &str) -> Vec<String> {
+    let mut paths = Vec::new();
+    let mut in_section = false;
+    
+    for line in output.lines() {
+        let line = line.trim();
+        if line.starts_with("Would ") {
+            in_section = line.starts_with(header);
+            continue;
+        }
+        if in_section {
+            if line.starts_with('/') {
+                paths.push(line.to_string());
+            } else if !line.is_empty() {
+                in_section = false;
+            }
+        }
+    }
+    
+    paths
+}
+
+/// 从 caveats 中提取 export 语句，去掉 `echo '...' >> ~/.zshrc` 包装
+fn parse_caveat_exports(caveats: &str) -> Vec<String> {
+    let mut exports: Vec<String> = Vec::new();
+    
+    for line in caveats.lines() {
+        let Some(start) = line.find("export ") else {
+            continue;
+        };
+        let mut statement = &line[start..];
+        if line[..start].trim_start().starts_with("echo '") {
+            if let Some(end) = statement.find("' >>") {
+                statement = &statement[..end];
+            }
+        }
+        let statement = statement.trim().to_string();
+        if !exports.contains(&statement) {
+            exports.push(statement);
+        }
+    }
+    
+    exports
+}
+
+/// 链接 keg 到 Homebrew 前缀（brew link）
+#[tauri::command]
+pub async fn link_package(
+    name: String,
+    options: Option<LinkOptions>,
+    window: tauri::Window,
+) -> Result<CommandOutput, String> {
+    let options = options.unwrap_or_default();
+    let mut args = vec!["link".to_string()];
+    if options.overwrite {
+        args.push("--overwrite".to_string());
+    }
+    if options.force {
+        args.push("--force".to_string());
+    }
+    args.push(name.clone());
+    
+    execute_brew_command_with_progress(&args, &window, &name, "link_package").await
+}
+
+/// 取消链接 keg（brew unlink）
+#[tauri::command]
+pub async fn unlink_package(name: String, window: tauri::Window) -> Result<CommandOutput, String> {
+    let args = vec!["unlink".to_string(), name.clone()];
+    execute_brew_command_with_progress(&args, &window, &name, "unlink_package").await
+}
+
+/// 预览链接操作：将创建哪些链接，以及哪些已有文件会冲突
+#[tauri::command]
+pub async fn preview_link(name: String, force: bool) -> Result<LinkPreview, String> {
+    let mut args = vec!["link", "--dry-run"];
+    if force {
+        args.push("--force");
+    }
+    args.push(&name);
+    let output = execute_brew_command(&args)?;
+    if !output.success {
+        return Err(format!("Failed to preview link: {}", output.stderr));
+    }
+    let would_link = parse_dry_run_paths(&output.stdout, "Would link");
+    let conflicts = link_conflicts(&name, force)?;
+    
+    Ok(LinkPreview {
+        name,
+        would_link,
+        conflicts,
+    })
+}
+
+/// 链接时会被 --overwrite 删除的已有文件（brew link --overwrite --dry-run）
+pub(crate) fn link_conflicts(name: &str, force: bool) -> Result<Vec<String>, String> {
+    let mut args = vec!["link", "--overwrite", "--dry-run"];
+    if force {
+        args.push("--force");
+    }
+    args.push(name);
+    let output = execute_brew_command(&args)?;
+    Ok(if output.success {
+        parse_dry_run_paths(&output.stdout, "Would remove")
+    } else {
+        vec![]
+    })
+}
+
+/// 预览取消链接操作将删除的符号链接
+#[tauri::command]
+pub async fn preview_unlink(name: String) -> Result<Vec<String>, String> {
+    let output = execute_brew_command(&["unlink", "--dry-run", &name])?;
+    if !output.success {
+        return Err(format!("Failed to preview unlink: {}", output.stderr));
+    }
+    
+    Ok(parse_dry_run_paths(&output.stdout, "Would remove"))
+}
+
+/// 列出已安装的 keg-only formula 及其所需的环境变量
+#[tauri::command]
+pub async fn list_keg_only() -> Result<Vec<KegOnlyFormula>, String> {
+    let state = installed_state()?;
+    
+    Ok(state
+        .formulae
+        .into_iter()
+        .filter(|f| f.keg_only)
+        .map(|f| KegOnlyFormula {
+            version: f
+                .installed
+                .first()
+                .map(|v| v.version.clone())
+                .unwrap_or_default(),
+            reason: f
+                .keg_only_reason
+                .map(|r| {
+                    if r.explanation.is_empty() {
+                        r.reason.trim_start_matches(':').replace('_', " ")
+                    } else {
+                        r.explanation
+                    }
+                })
+                .unwrap_or_default(),
+            exports: f
+                .caveats
+                .as_deref()
+                .map(parse_caveat_exports)
+                .unwrap_or_default(),
+            linked: f.linked_keg.is_some(),
+            name: f.name,
+        })
+        .collect())
+}
@@ -0,0 +1,202 @@
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeSet, HashMap};
+use std::io::{Read, Seek, SeekFrom};
+use std::sync::Mutex;
+use tauri::{AppHandle, State};
+
+use crate::history::{history_path, HistoryEntry, HistoryFilter, HistorySummary};
+
+const DEFAULT_RESULT_LIMIT: usize = 50;
+/// 每条记录最多返回的匹配行
+const MAX_SNIPPETS_PER_ENTRY: usize = 5;
+
+/// 已索引的历史输出：词 -> 包含该词的记录下标
+#[derive(Default)]
+struct LogIndex {
+    /// 已读取到的历史文件字节位置，用于增量索引
+    indexed_bytes: u64,
+    entries: Vec<HistoryEntry>,
+    tokens: HashMap<String, BTreeSet<usize>>,
+}
+
+/// 历史输出的全文索引，首次搜索时建立，之后随历史文件增长增量更新
+#[derive(Default)]
+pub struct HistoryLogIndex(Mutex<LogIndex>);
+
+/// 一行匹配的日志
+#[derive(Debug, Serialize, Deserialize)]
+pub struct LogSnippet {
+    /// stdout / stderr
+    pub stream: String,
+    /// 行号（从 1 开始）
+    pub line_number: usize,
+    pub text: String,
+    /// 匹配部分在 text 中的字节区间 [start, end)
+    pub highlights: Vec<(usize, usize)>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct LogSearchResult {
+    pub entry: HistorySummary,
+    pub snippets: Vec<LogSnippet>,
+    /// 该记录中匹配的总行数
+    pub match_count: usize,
+}
+
+/// 把文本拆成小写的词（字母、数字、下划线）
+fn tokenize(text: &str) -> impl Iterator<Item = String> + '_ {
+    text.split(|c: char| !c.is_alphanumeric() && c != '_')
+        .filter(|t| !t.is_empty())
+        .map(|t| t.to_lowercase())
+}
+
+impl LogIndex {
+    fn add(&mut self, entry: HistoryEntry) {
+        let index = self.entries.len();
+        for token in tokenize(&entry.stdout).chain(tokenize(&entry.stderr)) {
+            self.tokens.entry(token).or_default().insert(index);
+        }
+        self.entries.push(entry);
+    }
+    
+    /// 读取历史文件中新增的部分；文件变短（被替换）时重建索引
+    fn refresh(&mut self, app: &AppHandle) -> Result<(), String> {
+        let path = history_path(app)?;
+        let len = std::fs::metadata(&path).map(|m| m.len()).unwrap_or(0);
+        if len < self.indexed_bytes {
+            *self = LogIndex::default();
+        }
+        if len == self.indexed_bytes {
+            return Ok(());
+        }
+        
+        let mut file =
+            std::fs::File::open(&path).map_err(|e| format!("Failed to read history: {}", e))?;
+        file.seek(SeekFrom::Start(self.indexed_bytes))
+            .map_err(|e| format!("Failed to read history: {}", e))?;
+        let mut buf = Vec::new();
+        file.read_to_end(&mut buf)
+            .map_err(|e| format!("Failed to read history: {}", e))?;
+        
+        // 只处理完整的行，写了一半的行留到下次
+        let complete = buf.iter().rposition(|b| *b == b'\n').map_or(0, |p| p + 1);
+        for line in String::from_utf8_lossy(&buf[..complete]).lines() {
+            if let Ok(entry) = serde_json::from_str(line) {
+                self.add(entry);
+            }
+        }
+        self.indexed_bytes += complete as u64;
+        
+        Ok(())
+    }
+    
+    /// 包含全部查询词的记录
+    fn candidates(&self, terms: &[String]) -> BTreeSet<usize> {
+        let mut sets = terms
+            .iter()
+            .map(|t| self.tokens.get(t).cloned().unwrap_or_default());
+        let first = sets.next().unwrap_or_default();
+        sets.fold(first, |acc, set| acc.intersection(&set).copied().collect())
+    }
+}
+
+/// 查找一行中所有查询词出现的位置；有词没出现时返回 None
+fn line_highlights(line: &str, terms: &[String]) -> Option<Vec<(usize, usize)>> {
+    let lower = line.to_lowercase();
+    // 小写化可能改变非 ASCII 字符的字节长度，此时无法映射回原文位置
+    if lower.len() != line.len() {
+        return terms
+            .iter()
+            .all(|t| lower.contains(t.as_str()))
+            .then(Vec::new);
+    }
+    
+    let mut highlights = Vec::new();
+    for term in terms {
+        let found: Vec<(usize, usize)> = lower
+            .match_indices(term.as_str())
+            .map(|(start, m)| (start, start + m.len()))
+            .collect();
+        if found.is_empty() {
+            return None;
+        }
+        highlights.extend(found);
+    }
+    highlights.sort();
+    
+    Some(highlights)
+}
+
+fn search_entry(entry: &HistoryEntry, terms: &[String]) -> (Vec<LogSnippet>, usize) {
+    let mut snippets = Vec::new();
+    let mut match_count = 0;
+    
+    for (stream, text) in [("stdout", &entry.stdout), ("stderr", &entry.stderr)] {
+        for (index, line) in text.lines().enumerate() {
+            let Some(highlights) = line_highlights(line, terms) else {
+                continue;
+            };
+            match_count += 1;
+            if snippets.len() < MAX_SNIPPETS_PER_ENTRY {
+                snippets.push(LogSnippet {
+                    stream: stream.to_string(),
+                    line_number: index + 1,
+                    text: line.to_string(),
+                    highlights,
+                });
+            }
+        }
+    }
+    
+    (snippets, match_count)
+}
+
+/// 在历史操作的输出中全文搜索，返回匹配的记录和高亮的行
+#[tauri::command]
+pub async fn search_history_logs(
+    query: String,
+    filters: Option<HistoryFilter>,
+    oldest_first: Option<bool>,
+    limit: Option<usize>,
+    app: AppHandle,
+    index: State<'_, HistoryLogIndex>,
+) -> Result<Vec<LogSearchResult>, String> {
+    let terms: Vec<String> = tokenize(&query).collect();
+    if terms.is_empty() {
+        return Ok(vec![]);
+    }
+    let filters = filters.unwrap_or_default();
+    let limit = limit.unwrap_or(DEFAULT_RESULT_LIMIT);
+    
+    let mut index = index.0.lock().map_err(|e| e.to_string())?;
+    index.refresh(&app)?;
+    
+    let candidates = index.candidates(&terms);
+    let ordered: Box<dyn Iterator<Item = &usize>> = if oldest_first.unwrap_or(false) {
+        Box::new(candidates.iter())
+    } else {
+        Box::new(candidates.iter().rev())
+    };
+    
+    let mut results = Vec::new();
+    for &i in ordered {
+        let entry = &index.entries[i];
+        if !filters.matches(entry) {
+            continue;
+        }
+        let (snippets, match_count) = search_entry(entry, &terms);
+        if match_count == 0 {
+            continue;
+        }
+        results.push(LogSearchResult {
+            entry: HistorySummary::from(entry.clone()),
+            snippets,
+            match_count,
+        });
+        if results.len() >= limit {
+            break;
+        }
+    }
+    
+    Ok(results)
+}
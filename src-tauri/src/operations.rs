@@ -0,0 +1,76 @@
+use std::sync::{Condvar, Mutex};
+use tauri::AppHandle;
+
+use crate::history;
+use crate::homebrew::CommandOutput;
+
+/// 排队号：next 为下一个取号，serving 为当前可以执行的号
+struct Tickets {
+    next: u64,
+    serving: u64,
+}
+
+/// 会修改 Homebrew 状态的操作队列：同一时间只允许一个操作执行，其余按取号顺序等待
+static TICKETS: Mutex<Tickets> = Mutex::new(Tickets {
+    next: 0,
+    serving: 0,
+});
+static TURN: Condvar = Condvar::new();
+
+/// 持有期间独占操作队列，释放时轮到下一个号
+pub(crate) struct QueueGuard(());
+
+impl Drop for QueueGuard {
+    fn drop(&mut self) {
+        // 之前的操作 panic 不影响后续操作
+        let mut tickets = TICKETS.lock().unwrap_or_else(|e| e.into_inner());
+        tickets.serving += 1;
+        TURN.notify_all();
+    }
+}
+
+/// 进入操作队列，按到达顺序执行；返回的 guard 释放时下一个操作才会开始
+pub(crate) fn enter_queue() -> QueueGuard {
+    let mut tickets = TICKETS.lock().unwrap_or_else(|e| e.into_inner());
+    let ticket = tickets.next;
+    tickets.next += 1;
+    while tickets.serving != ticket {
+        tickets = TURN.wait(tickets).unwrap_or_else(|e| e.into_inner());
+    }
+    QueueGuard(())
+}
+
+/// 在操作队列中逐个删除文件并记录到操作历史，删除前自动拍快照；remove 返回的错误写入 stderr
+pub(crate) fn remove_paths<F>(
+    app: &AppHandle,
+    action: &str,
+    package: &str,
+    paths: Vec<String>,
+    mut remove: F,
+) -> CommandOutput
+where
+    F: FnMut(&str) -> Result<(), String>,
+{
+    let _queue = enter_queue();
+    crate::snapshots::auto_snapshot(app, action, package);
+    let started_at = history::now_millis();
+    let mut stdout = String::new();
+    let mut stderr = String::new();
+    for path in &paths {
+        match remove(path) {
+            Ok(()) => stdout.push_str(&format!("Removed {}\n", path)),
+            Err(e) => stderr.push_str(&format!("{}\n", e)),
+        }
+    }
+    
+    let success = stderr.is_empty();
+    let output = CommandOutput {
+        success,
+        stdout,
+        stderr,
+        exit_code: if success { 0 } else { 1 },
+    };
+    let argv = std::iter::once("rm".to_string()).chain(paths).collect();
+    history::record_external(app, action, package, argv, started_at, &output);
+    output
+}
@@ -0,0 +1,299 @@
+use serde::{Deserialize, Serialize};
+use tauri::AppHandle;
+
+use crate::homebrew::{execute_brew_command_queued, get_brew_prefix, package_args, CommandOutput};
+use crate::operations::{self, QueueGuard};
+use crate::snapshots::{auto_snapshot, capture_state, load_snapshot, Snapshot, SnapshotFormula};
+use crate::versions::{activate_keg, find_cached_bottles, install_cached_bottle, list_kegs};
+
+/// 回滚中的一个步骤
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RollbackStep {
+    /// tap / untap / uninstall / relink / install_bottle / install / reinstall / unsupported
+    pub action: String,
+    pub name: String,
+    #[serde(rename = "type")]
+    pub pkg_type: String,
+    pub current_version: Option<String>,
+    pub target_version: Option<String>,
+    /// 目标版本的来源：keg（Cellar 中的旧 keg）/ cache（缓存的 bottle）/ network / none
+    pub source: String,
+    /// 本地文件路径（缓存的 bottle）
+    pub source_path: Option<String>,
+    /// 无需联网即可完成
+    pub offline: bool,
+    /// 能恢复到快照中的确切版本
+    pub exact: bool,
+    pub note: String,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct RollbackPlan {
+    pub snapshot_id: String,
+    pub snapshot_label: String,
+    pub steps: Vec<RollbackStep>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct RollbackStepResult {
+    pub step: RollbackStep,
+    pub success: bool,
+    pub message: String,
+}
+
+fn step(action: &str, name: &str, pkg_type: &str) -> RollbackStep {
+    RollbackStep {
+        action: action.to_string(),
+        name: name.to_string(),
+        pkg_type: pkg_type.to_string(),
+        current_version: None,
+        target_version: None,
+        source: "none".to_string(),
+        source_path: None,
+        offline: true,
+        exact: true,
+        note: String::new(),
+    }
+}
+
+/// 恢复 formula 到指定版本的步骤：优先使用 Cellar 中的旧 keg，其次缓存的 bottle，最后联网安装最新版
+fn restore_formula_step(
+    prefix: &str,
+    name: &str,
+    version: &str,
+    current: Option<&str>,
+) -> RollbackStep {
+    let installed = current.is_some();
+    let mut s = step("install", name, "formula");
+    s.current_version = current.map(|v| v.to_string());
+    s.target_version = Some(version.to_string());
+    
+    if installed && list_kegs(prefix, name).iter().any(|k| k == version) {
+        s.action = "relink".to_string();
+        s.source = "keg".to_string();
+        s.note = "Old keg is still in the Cellar".to_string();
+    } else if let Some(bottle) = find_cached_bottles(name)
+        .into_iter()
+        .find(|b| b.version == version)
+    {
+        s.action = "install_bottle".to_string();
+        s.source = "cache".to_string();
+        s.source_path = Some(bottle.path);
+        s.note = "Bottle is still in the download cache".to_string();
+    } else if let Some(current) = current {
+        // 联网只能装到最新版，而已安装的 formula 再 install 不会有任何变化
+        s.action = "unsupported".to_string();
+        s.offline = false;
+        s.exact = false;
+        s.note = format!(
+            "{} {} is no longer available locally; {} stays installed",
+            name, version, current
+        );
+    } else {
+        s.source = "network".to_string();
+        s.offline = false;
+        s.exact = false;
+        s.note = format!(
+            "{} {} is no longer available locally; the current version will be installed",
+            name, version
+        );
+    }
+    
+    s
+}
+
+/// 排列卸载顺序：一个 formula 只有在没有其他待卸载的 formula 依赖它时才卸载
+fn uninstall_order(mut remaining: Vec<&SnapshotFormula>) -> Vec<&SnapshotFormula> {
+    // 依赖可能写成 `user/tap/name`
+    let depends_on = |f: &SnapshotFormula, name: &str| {
+        f.dependencies
+            .iter()
+            .any(|d| d.rsplit('/').next() == Some(name))
+    };
+    
+    let mut ordered = Vec::with_capacity(remaining.len());
+    while !remaining.is_empty() {
+        let (ready, blocked): (Vec<_>, Vec<_>) = remaining.iter().partition(|f| {
+            !remaining
+                .iter()
+                .any(|other| other.name != f.name && depends_on(other, &f.name))
+        });
+        if ready.is_empty() {
+            // 循环依赖：按原顺序卸载剩下的
+            ordered.append(&mut remaining);
+            break;
+        }
+        ordered.extend(ready);
+        remaining = blocked;
+    }
+    ordered
+}
+
+/// 计算从当前状态回到快照状态所需的步骤
+fn compute_plan(target: &Snapshot, current: &Snapshot) -> Result<RollbackPlan, String> {
+    let prefix = get_brew_prefix()?;
+    let mut steps = Vec::new();
+    
+    for tap in target.taps.iter().filter(|t| !current.taps.contains(t)) {
+        let mut s = step("tap", tap, "tap");
+        s.offline = false;
+        s.source = "network".to_string();
+        steps.push(s);
+    }
+    
+    // 版本变化或被删除的 formula：恢复到快照中链接的版本
+    for f in &target.formulae {
+        let Some(version) = f
+            .linked_version
+            .clone()
+            .or_else(|| f.versions.last().cloned())
+        else {
+            continue;
+        };
+        let existing = current.formulae.iter().find(|c| c.name == f.name);
+        let current_version = existing.and_then(|c| {
+            c.linked_version
+                .clone()
+                .or_else(|| c.versions.last().cloned())
+        });
+        if current_version.as_deref() == Some(version.as_str()) {
+            continue;
+        }
+        steps.push(restore_formula_step(
+            &prefix,
+            &f.name,
+            &version,
+            current_version.as_deref(),
+        ));
+    }
+    
+    // cask 只能重新安装当前版本
+    for c in &target.casks {
+        let existing = current.casks.iter().find(|x| x.name == c.name);
+        if existing.is_some_and(|x| x.version == c.version) {
+            continue;
+        }
+        let mut s = step(
+            if existing.is_some() {
+                "reinstall"
+            } else {
+                "install"
+            },
+            &c.name,
+            "cask",
+        );
+        s.current_version = existing.map(|x| x.version.clone());
+        s.target_version = Some(c.version.clone());
+        s.source = "network".to_string();
+        s.offline = false;
+        s.exact = false;
+        s.note = "Casks can only be reinstalled at their current version".to_string();
+        steps.push(s);
+    }
+    
+    // 之后新增的包：在恢复版本之后卸载，此时新版本引入的依赖已没有依赖方；
+    // cask 可能依赖 formula，先卸载 cask，formula 按依赖方先于被依赖的包的顺序卸载
+    for c in &current.casks {
+        if !target.casks.iter().any(|t| t.name == c.name) {
+            let mut s = step("uninstall", &c.name, "cask");
+            s.current_version = Some(c.version.clone());
+            steps.push(s);
+        }
+    }
+    let added: Vec<&SnapshotFormula> = current
+        .formulae
+        .iter()
+        .filter(|f| !target.formulae.iter().any(|t| t.name == f.name))
+        .collect();
+    for f in uninstall_order(added) {
+        let mut s = step("uninstall", &f.name, "formula");
+        s.current_version = f.versions.last().cloned();
+        steps.push(s);
+    }
+    
+    for tap in current.taps.iter().filter(|t| !target.taps.contains(t)) {
+        steps.push(step("untap", tap, "tap"));
+    }
+    
+    Ok(RollbackPlan {
+        snapshot_id: target.id.clone(),
+        snapshot_label: target.label.clone(),
+        steps,
+    })
+}
+
+/// 计算回到某个快照所需的操作，并标出无法离线恢复的条目
+#[tauri::command]
+pub async fn plan_rollback(snapshot_id: String, app: AppHandle) -> Result<RollbackPlan, String> {
+    let target = load_snapshot(&app, &snapshot_id)?;
+    let current = capture_state("current", false)?;
+    compute_plan(&target, &current)
+}
+
+async fn run_step(
+    queue: &QueueGuard,
+    step: &RollbackStep,
+    target: &Snapshot,
+    window: &tauri::Window,
+) -> Result<CommandOutput, String> {
+    const ACTION: &str = "execute_rollback";
+    let is_cask = step.pkg_type == "cask";
+    
+    let args: Vec<String> = match step.action.as_str() {
+        "tap" => vec!["tap".to_string(), step.name.clone()],
+        "untap" => vec!["untap".to_string(), step.name.clone()],
+        "uninstall" => package_args("uninstall", &step.name, is_cask),
+        "reinstall" => package_args("reinstall", &step.name, is_cask),
+        "install" => package_args("install", &step.name, is_cask),
+        "unsupported" => return Err(step.note.clone()),
+        "install_bottle" => {
+            let path = step.source_path.as_deref().unwrap_or_default();
+            return install_cached_bottle(queue, &step.name, path, window, ACTION).await;
+        }
+        "relink" => {
+            let version = step.target_version.as_deref().unwrap_or_default();
+            let linked = target
+                .formulae
+                .iter()
+                .any(|f| f.name == step.name && f.linked_version.as_deref() == Some(version));
+            return activate_keg(queue, &step.name, version, linked, window, ACTION).await;
+        }
+        other => return Err(format!("Unknown rollback step: {}", other)),
+    };
+    
+    execute_brew_command_queued(queue, &args, window, &step.name, ACTION).await
+}
+
+/// 执行回滚计划，返回每一步的结果
+#[tauri::command]
+pub async fn execute_rollback(
+    snapshot_id: String,
+    app: AppHandle,
+    window: tauri::Window,
+) -> Result<Vec<RollbackStepResult>, String> {
+    let target = load_snapshot(&app, &snapshot_id)?;
+    // 整个回滚期间独占操作队列，计划基于的状态不会被其他操作改变
+    let queue = operations::enter_queue();
+    auto_snapshot(&app, "execute_rollback", &snapshot_id);
+    let current = capture_state("current", false)?;
+    let plan = compute_plan(&target, &current)?;
+    
+    let mut results = Vec::new();
+    for step in plan.steps {
+        let (success, message) = match run_step(&queue, &step, &target, &window).await {
+            Ok(o) if o.success => (true, String::new()),
+            Ok(o) => (
+                false,
+                o.stderr.lines().last().unwrap_or_default().to_string(),
+            ),
+            Err(e) => (false, e),
+        };
+        results.push(RollbackStepResult {
+            step,
+            success,
+            message,
+        });
+    }
+    
+    Ok(results)
+}
@@ -0,0 +1,564 @@
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use std::time::UNIX_EPOCH;
+use tauri::{AppHandle, Manager};
+
+use crate::homebrew::{get_brew_cache, installed_packages, Package};
+
+/// 单次搜索最多返回的结果数
+const MAX_RESULTS: usize = 200;
+
+/// `*.jws.json` 外层：payload 是 JSON 字符串
+#[derive(Debug, Deserialize)]
+struct JwsFile {
+    payload: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct ApiFormula {
+    name: String,
+    full_name: String,
+    #[serde(default)]
+    aliases: Vec<String>,
+    #[serde(default)]
+    oldnames: Vec<String>,
+    oldname: Option<String>,
+    desc: Option<String>,
+    versions: ApiFormulaVersions,
+    #[serde(default)]
+    revision: u32,
+    service: Option<serde_json::Value>,
+}
+
+#[derive(Debug, Deserialize)]
+struct ApiFormulaVersions {
+    stable: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+struct ApiCask {
+    token: String,
+    full_token: String,
+    #[serde(default)]
+    old_tokens: Vec<String>,
+    /// 应用显示名，例如 "Visual Studio Code"
+    #[serde(default)]
+    name: Vec<String>,
+    desc: Option<String>,
+    version: Option<String>,
+}
+
+/// 索引中的一个 formula 或 cask
+#[derive(Debug, Clone)]
+pub(crate) struct IndexedPackage {
+    pub name: String,
+    pub full_name: String,
+    /// 别名、旧名称和 cask 的显示名
+    pub aliases: Vec<String>,
+    pub description: Option<String>,
+    pub version: String,
+    pub is_cask: bool,
+    pub has_service: bool,
+}
+
+#[derive(Default)]
+struct SearchIndexState {
+    /// API 文件的修改时间，变化时重新加载
+    stamp: Vec<u128>,
+    packages: Arc<Vec<IndexedPackage>>,
+    /// list_installed 的结果，键为 `formula:name` / `cask:name`
+    installed: Option<Arc<HashMap<String, Package>>>,
+    /// 已安装列表每次更新或丢弃时递增，避免用过期的结果覆盖
+    installed_generation: u64,
+}
+
+/// 基于下载缓存中 Homebrew API 文件的内存搜索索引
+#[derive(Default)]
+pub struct SearchIndex(Mutex<SearchIndexState>);
+
+fn installed_key(name: &str, is_cask: bool) -> String {
+    format!("{}:{}", if is_cask { "cask" } else { "formula" }, name)
+}
+
+fn api_files() -> Result<[PathBuf; 2], String> {
+    let api = Path::new(&get_brew_cache()?).join("api");
+    Ok([api.join("formula.jws.json"), api.join("cask.jws.json")])
+}
+
+fn file_stamp(path: &Path) -> u128 {
+    std::fs::metadata(path)
+        .and_then(|m| m.modified())
+        .ok()
+        .and_then(|t| t.duration_since(UNIX_EPOCH).ok())
+        .map(|d| d.as_nanos())
+        .unwrap_or(0)
+}
+
+fn read_payload<T: for<'de> Deserialize<'de>>(path: &Path) -> Result<Vec<T>, String> {
+    let content = std::fs::read_to_string(path)
+        .map_err(|e| format!("Failed to read {}: {}", path.display(), e))?;
+    let jws: JwsFile = serde_json::from_str(&content)
+        .map_err(|e| format!("Failed to parse {}: {}", path.display(), e))?;
+    serde_json::from_str(&jws.payload)
+        .map_err(|e| format!("Failed to parse {} payload: {}", path.display(), e))
+}
+
+fn load_packages(formula_path: &Path, cask_path: &Path) -> Result<Vec<IndexedPackage>, String> {
+    let mut packages = Vec::new();
+    if formula_path.exists() {
+        for f in read_payload::<ApiFormula>(formula_path)? {
+            let mut version = f.versions.stable.unwrap_or_default();
+            if f.revision > 0 {
+                version = format!("{}_{}", version, f.revision);
+            }
+            let mut aliases = f.aliases;
+            aliases.extend(f.oldnames);
+            aliases.extend(f.oldname);
+            packages.push(IndexedPackage {
+                name: f.name,
+                full_name: f.full_name,
+                aliases,
+                description: f.desc,
+                version,
+                is_cask: false,
+                has_service: f.service.is_some(),
+            });
+        }
+    }
+    if cask_path.exists() {
+        for c in read_payload::<ApiCask>(cask_path)? {
+            let mut aliases = c.old_tokens;
+            aliases.extend(c.name);
+            packages.push(IndexedPackage {
+                name: c.token,
+                full_name: c.full_token,
+                aliases,
+                description: c.desc,
+                version: c.version.unwrap_or_default(),
+                is_cask: true,
+                has_service: false,
+            });
+        }
+    }
+    Ok(packages)
+}
+
+/// 记录最新的已安装列表，供搜索结果合并安装状态
+pub(crate) fn remember_installed(app: &AppHandle, packages: &[Package]) {
+    let index = app.state::<SearchIndex>();
+    let Ok(mut state) = index.0.lock() else {
+        return;
+    };
+    state.installed = Some(Arc::new(installed_map(packages.iter().cloned())));
+    state.installed_generation += 1;
+}
+
+/// 安装状态变化后丢弃已安装列表，下次搜索时重新获取
+pub(crate) fn forget_installed(app: &AppHandle) {
+    let index = app.state::<SearchIndex>();
+    let Ok(mut state) = index.0.lock() else {
+        return;
+    };
+    state.installed = None;
+    state.installed_generation += 1;
+}
+
+fn installed_map(packages: impl Iterator<Item = Package>) -> HashMap<String, Package> {
+    packages
+        .map(|p| (installed_key(&p.name, p.pkg_type == "cask"), p))
+        .collect()
+}
+
+/// 取得已安装列表；没有缓存时在锁外调用 brew 获取，失败时不缓存
+fn cached_installed(app: &AppHandle) -> Option<Arc<HashMap<String, Package>>> {
+    let index = app.state::<SearchIndex>();
+    let generation = {
+        let state = index.0.lock().ok()?;
+        if let Some(installed) = &state.installed {
+            return Some(installed.clone());
+        }
+        state.installed_generation
+    };
+    
+    let installed = Arc::new(installed_map(installed_packages().ok()?.into_iter()));
+    if let Ok(mut state) = index.0.lock() {
+        if state.installed_generation == generation {
+            state.installed = Some(installed.clone());
+        }
+    }
+    Some(installed)
+}
+
+/// 用已安装列表中的信息替换搜索到的包，保留本地缺少的描述和服务信息
+fn merge_installed(package: Package, installed: Option<&HashMap<String, Package>>) -> Package {
+    let key = installed_key(&package.name, package.pkg_type == "cask");
+    match installed.and_then(|i| i.get(&key)) {
+        Some(local) => Package {
+            description: local.description.clone().or(package.description),
+            has_service: local.has_service || package.has_service,
+            ..local.clone()
+        },
+        None => package,
+    }
+}
+
+/// 文本中命中查询的位置，供界面高亮
+#[derive(Debug, Clone, Serialize)]
+pub struct SearchMatch {
+    /// name、alias 或 description
+    pub field: String,
+    pub value: String,
+    /// exact、prefix、token、substring、fuzzy；描述为 word（全部查询词命中）或 partial
+    pub kind: String,
+    /// 命中的字符区间 [start, end)
+    pub ranges: Vec<(usize, usize)>,
+}
+
+/// 带评分的搜索结果
+#[derive(Debug, Clone, Serialize)]
+pub struct SearchResult {
+    #[serde(flatten)]
+    pub package: Package,
+    /// 索引中的最新版本；已安装的包的 version 是本地版本
+    #[serde(rename = "latestVersion")]
+    pub latest_version: Option<String>,
+    pub score: u32,
+    pub matches: Vec<SearchMatch>,
+}
+
+/// 按非字母数字字符切分，返回每个词及其字符区间
+fn tokens(text: &str) -> Vec<(String, usize, usize)> {
+    let mut tokens = Vec::new();
+    let mut current = String::new();
+    let mut start = 0;
+    for (i, c) in text.chars().enumerate() {
+        if c.is_alphanumeric() {
+            if current.is_empty() {
+                start = i;
+            }
+            current.push(c);
+        } else if !current.is_empty() {
+            tokens.push((std::mem::take(&mut current), start, i));
+        }
+    }
+    if !current.is_empty() {
+        let end = start + current.chars().count();
+        tokens.push((current, start, end));
+    }
+    tokens
+}
+
+/// 转为小写，并记录小写文本中每个字符对应的原文字符位置（例如 `İ` 会变成两个字符）
+fn lowercase_with_map(value: &str) -> (String, Vec<usize>) {
+    let mut lower = String::new();
+    let mut map = Vec::new();
+    for (i, c) in value.chars().enumerate() {
+        for l in c.to_lowercase() {
+            lower.push(l);
+            map.push(i);
+        }
+    }
+    (lower, map)
+}
+
+/// 把小写文本中的字符区间换算回原文
+fn original_range(map: &[usize], (start, end): (usize, usize)) -> (usize, usize) {
+    match (map.get(start), end.checked_sub(1).and_then(|e| map.get(e))) {
+        (Some(&start), Some(&last)) if start <= last => (start, last + 1),
+        _ => (0, 0),
+    }
+}
+
+/// 编辑距离（按字符，相邻字符交换计为一次）
+fn edit_distance(a: &str, b: &str) -> usize {
+    let a: Vec<char> = a.chars().collect();
+    let b: Vec<char> = b.chars().collect();
+    let mut rows = vec![vec![0; b.len() + 1]; a.len() + 1];
+    for (i, row) in rows.iter_mut().enumerate() {
+        row[0] = i;
+    }
+    for (j, cell) in rows[0].iter_mut().enumerate() {
+        *cell = j;
+    }
+    for i in 1..=a.len() {
+        for j in 1..=b.len() {
+            let cost = usize::from(a[i - 1] != b[j - 1]);
+            let mut best = (rows[i - 1][j] + 1)
+                .min(rows[i][j - 1] + 1)
+                .min(rows[i - 1][j - 1] + cost);
+            if i > 1 && j > 1 && a[i - 1] == b[j - 2] && a[i - 2] == b[j - 1] {
+                best = best.min(rows[i - 2][j - 2] + 1);
+            }
+            rows[i][j] = best;
+        }
+    }
+    rows[a.len()][b.len()]
+}
+
+/// 名称或别名的匹配：完全相同 > 前缀 > 词相同 > 词前缀 > 子串，返回 0-100 的基础分
+fn match_text(field: &str, value: &str, query: &str) -> Option<(u32, SearchMatch)> {
+    let (lower, map) = lowercase_with_map(value);
+    let query_len = query.chars().count();
+    let found = |kind: &str, ranges: Vec<(usize, usize)>| SearchMatch {
+        field: field.to_string(),
+        value: value.to_string(),
+        kind: kind.to_string(),
+        ranges: ranges
+            .into_iter()
+            .map(|r| original_range(&map, r))
+            .collect(),
+    };
+    
+    if lower == query {
+        return Some((100, found("exact", vec![(0, query_len)])));
+    }
+    if lower.starts_with(query) {
+        return Some((80, found("prefix", vec![(0, query_len)])));
+    }
+    let words = tokens(&lower);
+    if let Some((_, start, end)) = words.iter().find(|(w, _, _)| w == query) {
+        return Some((70, found("token", vec![(*start, *end)])));
+    }
+    if let Some((_, start, _)) = words.iter().find(|(w, _, _)| w.starts_with(query)) {
+        return Some((60, found("token", vec![(*start, start + query_len)])));
+    }
+    let byte = lower.find(query)?;
+    let start = lower[..byte].chars().count();
+    Some((40, found("substring", vec![(start, start + query_len)])))
+}
+
+/// 描述的匹配：按命中的查询词比例计分，全部命中时为 20
+fn match_description(value: &str, terms: &[&str]) -> Option<(u32, SearchMatch)> {
+    let (lower, map) = lowercase_with_map(value);
+    let words = tokens(&lower);
+    let mut ranges = Vec::new();
+    let mut hit = 0;
+    for term in terms {
+        let term_len = term.chars().count();
+        let before = ranges.len();
+        for (_, start, _) in words.iter().filter(|(w, _, _)| w.starts_with(term)) {
+            ranges.push((*start, start + term_len));
+        }
+        if ranges.len() > before {
+            hit += 1;
+        }
+    }
+    if hit == 0 {
+        return None;
+    }
+    let mut ranges: Vec<(usize, usize)> = ranges
+        .into_iter()
+        .map(|r| original_range(&map, r))
+        .collect();
+    ranges.sort();
+    let score = (20 * hit / terms.len()) as u32;
+    let kind = if hit == terms.len() {
+        "word"
+    } else {
+        "partial"
+    };
+    Some((
+        score,
+        SearchMatch {
+            field: "description".to_string(),
+            value: value.to_string(),
+            kind: kind.to_string(),
+            ranges,
+        },
+    ))
+}
+
+/// 拼写容错：查询与名称、去掉 `@版本` 的名称、名称中的词或等长前缀的最小编辑距离
+fn match_fuzzy(name: &str, query: &str) -> Option<(u32, SearchMatch)> {
+    let query_len = query.chars().count();
+    if query_len < 3 {
+        return None;
+    }
+    let allowed = if query_len <= 5 { 1 } else { 2 };
+    let (lower, map) = lowercase_with_map(name);
+    let lower_len = lower.chars().count();
+    // 每个候选及其在小写名称中的字符区间
+    let base = lower.split('@').next().unwrap_or(&lower).to_string();
+    let base_len = base.chars().count();
+    let mut candidates = vec![(lower.clone(), (0, lower_len)), (base, (0, base_len))];
+    candidates.extend(
+        tokens(&lower)
+            .into_iter()
+            .map(|(w, start, end)| (w, (start, end))),
+    );
+    for len in [query_len, query_len + 1] {
+        candidates.push((lower.chars().take(len).collect(), (0, len.min(lower_len))));
+    }
+    
+    // 长度相差太多的候选即使距离小也不像拼写错误，例如 vscode 与 code
+    let (distance, range) = candidates
+        .iter()
+        .filter(|(c, _)| c.chars().count().abs_diff(query_len) <= 1)
+        .map(|(c, range)| (edit_distance(query, c), *range))
+        .min_by_key(|(distance, _)| *distance)?;
+    if distance == 0 || distance > allowed {
+        return None;
+    }
+    Some((
+        (30 - 10 * distance) as u32,
+        SearchMatch {
+            field: "name".to_string(),
+            value: name.to_string(),
+            kind: "fuzzy".to_string(),
+            ranges: vec![original_range(&map, range)],
+        },
+    ))
+}
+
+/// 计算包的得分：名称权重最高，其次别名，描述相关度叠加，都不命中时尝试拼写容错
+fn score_package(
+    name: &str,
+    full_name: &str,
+    aliases: &[String],
+    description: Option<&str>,
+    query: &str,
+) -> Option<(u32, Vec<SearchMatch>)> {
+    let mut matches = Vec::new();
+    let mut best = 0;
+    
+    let name_match = match_text("name", name, query).or_else(|| {
+        (full_name != name)
+            .then(|| match_text("name", full_name, query))
+            .flatten()
+    });
+    if let Some((score, found)) = name_match {
+        best = score * 10;
+        matches.push(found);
+    }
+    if let Some((score, found)) = aliases
+        .iter()
+        .filter_map(|a| match_text("alias", a, query))
+        .max_by_key(|(score, _)| *score)
+    {
+        best = best.max(score * 7);
+        matches.push(found);
+    }
+    if matches.is_empty() {
+        if let Some((score, found)) = match_fuzzy(name, query) {
+            best = score * 10;
+            matches.push(found);
+        }
+    }
+    
+    let terms: Vec<&str> = query.split_whitespace().collect();
+    if let Some((score, found)) = description.and_then(|d| match_description(d, &terms)) {
+        best += score * 10;
+        matches.push(found);
+    }
+    
+    (!matches.is_empty()).then_some((best, matches))
+}
+
+fn sort_results(results: &mut [SearchResult]) {
+    results.sort_by(|a, b| {
+        b.score
+            .cmp(&a.score)
+            .then_with(|| a.package.name.len().cmp(&b.package.name.len()))
+            .then_with(|| a.package.name.cmp(&b.package.name))
+    });
+}
+
+/// 给 brew search 返回的包评分排序；brew 命中但这里不命中的排在最后
+pub(crate) fn rank_packages(
+    app: &AppHandle,
+    packages: Vec<Package>,
+    query: &str,
+) -> Vec<SearchResult> {
+    let query = query.trim().to_lowercase();
+    let installed = cached_installed(app);
+    let mut results: Vec<SearchResult> = packages
+        .into_iter()
+        .map(|package| merge_installed(package, installed.as_deref()))
+        .map(|package| {
+            let (score, matches) = score_package(
+                &package.name,
+                &package.name,
+                &[],
+                package.description.as_deref(),
+                &query,
+            )
+            .unwrap_or_default();
+            SearchResult {
+                package,
+                latest_version: None,
+                score,
+                matches,
+            }
+        })
+        .collect();
+    sort_results(&mut results);
+    results
+}
+
+/// 在缓存的 API 索引中搜索并按得分排序；没有 API 文件时返回 None
+pub(crate) fn search(app: &AppHandle, query: &str) -> Result<Option<Vec<SearchResult>>, String> {
+    let [formula_path, cask_path] = api_files()?;
+    let stamp = vec![file_stamp(&formula_path), file_stamp(&cask_path)];
+    if stamp.iter().all(|s| *s == 0) {
+        return Ok(None);
+    }
+    
+    // 加载和评分都在锁外进行，避免并发的搜索互相阻塞
+    let index = app.state::<SearchIndex>();
+    let cached = {
+        let state = index.0.lock().map_err(|e| e.to_string())?;
+        (state.stamp == stamp).then(|| state.packages.clone())
+    };
+    let packages = match cached {
+        Some(packages) => packages,
+        None => {
+            let packages = Arc::new(load_packages(&formula_path, &cask_path)?);
+            let mut state = index.0.lock().map_err(|e| e.to_string())?;
+            state.packages = packages.clone();
+            state.stamp = stamp;
+            packages
+        }
+    };
+    let installed = cached_installed(app);
+    
+    let query = query.trim().to_lowercase();
+    let mut hits: Vec<(u32, Vec<SearchMatch>, &IndexedPackage)> = packages
+        .iter()
+        .filter_map(|p| {
+            let description = p.description.as_deref();
+            score_package(&p.name, &p.full_name, &p.aliases, description, &query)
+                .map(|(score, matches)| (score, matches, p))
+        })
+        .collect();
+    hits.sort_by(|a, b| {
+        b.0.cmp(&a.0)
+            .then_with(|| a.2.name.len().cmp(&b.2.name.len()))
+            .then_with(|| a.2.name.cmp(&b.2.name))
+    });
+    
+    let results = hits
+        .into_iter()
+        .take(MAX_RESULTS)
+        .map(|(score, matches, p)| {
+            let package = Package {
+                name: p.name.clone(),
+                version: p.version.clone(),
+                pkg_type: if p.is_cask { "cask" } else { "formula" }.to_string(),
+                installed: false,
+                outdated: false,
+                description: p.description.clone(),
+                linked: None,
+                has_service: p.has_service,
+            };
+            SearchResult {
+                package: merge_installed(package, installed.as_deref()),
+                latest_version: Some(p.version.clone()).filter(|v| !v.is_empty()),
+                score,
+                matches,
+            }
+        })
+        .collect();
+    Ok(Some(results))
+}
@@ -0,0 +1,349 @@
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fs::File;
+use std::io::{Read, Seek, SeekFrom};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tauri::{AppHandle, Emitter, State};
+
+use crate::homebrew::{
+    execute_brew_command, execute_brew_command_with_progress, get_brew_prefix, CommandOutput,
+};
+
+/// brew services 管理的后台服务
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct ServiceInfo {
+    pub name: String,
+    /// started / stopped / scheduled / error / none / unknown
+    pub status: String,
+    pub user: Option<String>,
+    /// launchd plist 或 systemd unit 文件路径
+    pub file: Option<String>,
+    pub exit_code: Option<i32>,
+}
+
+/// 获取所有服务的状态（brew services list --json）
+pub(crate) fn list_services_internal() -> Result<Vec<ServiceInfo>, String> {
+    let output = execute_brew_command(&["services", "list", "--json"])?;
+    
+    if !output.success {
+        return Err(format!("Failed to list services: {}", output.stderr));
+    }
+    
+    if output.stdout.trim().is_empty() {
+        return Ok(vec![]);
+    }
+    
+    serde_json::from_str(&output.stdout)
+        .map_err(|e| format!("Failed to parse services JSON: {}", e))
+}
+
+/// 列出所有服务
+#[tauri::command]
+pub async fn list_services() -> Result<Vec<ServiceInfo>, String> {
+    list_services_internal()
+}
+
+async fn run_services_subcommand(
+    subcommand: &str,
+    name: String,
+    window: tauri::Window,
+) -> Result<CommandOutput, String> {
+    let args = vec!["services".to_string(), subcommand.to_string(), name.clone()];
+    execute_brew_command_with_progress(&args, &window, &name, &format!("{}_service", subcommand))
+        .await
+}
+
+/// 启动服务并设置为开机/登录自启（brew services start）
+#[tauri::command]
+pub async fn start_service(name: String, window: tauri::Window) -> Result<CommandOutput, String> {
+    run_services_subcommand("start", name, window).await
+}
+
+/// 停止服务（brew services stop）
+#[tauri::command]
+pub async fn stop_service(name: String, window: tauri::Window) -> Result<CommandOutput, String> {
+    run_services_subcommand("stop", name, window).await
+}
+
+/// 重启服务（brew services restart）
+#[tauri::command]
+pub async fn restart_service(name: String, window: tauri::Window) -> Result<CommandOutput, String> {
+    run_services_subcommand("restart", name, window).await
+}
+
+/// 仅运行服务，不设置自启（brew services run）
+#[tauri::command]
+pub async fn run_service(name: String, window: tauri::Window) -> Result<CommandOutput, String> {
+    run_services_subcommand("run", name, window).await
+}
+
+/// 正在跟踪的服务日志，值为停止标志
+#[derive(Default)]
+pub struct ServiceLogTails(Mutex<HashMap<String, Arc<AtomicBool>>>);
+
+/// 服务状态轮询器的停止标志
+#[derive(Default)]
+pub struct ServiceMonitor(Mutex<Option<Arc<AtomicBool>>>);
+
+/// 跟踪日志时首次输出的历史行数
+const LOG_BACKLOG_LINES: usize = 100;
+const LOG_POLL_INTERVAL: Duration = Duration::from_millis(500);
+const DEFAULT_MONITOR_INTERVAL_SECS: u64 = 10;
+
+/// 解析 formula service 块中声明的日志路径
+fn resolve_service_log_paths(name: &str) -> Result<Vec<PathBuf>, String> {
+    let output = execute_brew_command(&["info", "--json=v2", name])?;
+    
+    if !output.success {
+        return Err(format!("Failed to get package info: {}", output.stderr));
+    }
+    
+    #[derive(Debug, Deserialize)]
+    struct InfoJson {
+        formulae: Vec<FormulaService>,
+    }
+    
+    #[derive(Debug, Deserialize)]
+    struct FormulaService {
+        service: Option<ServiceBlock>,
+    }
+    
+    #[derive(Debug, Deserialize)]
+    struct ServiceBlock {
+        log_path: Option<String>,
+        error_log_path: Option<String>,
+    }
+    
+    let json: InfoJson = serde_json::from_str(&output.stdout)
+        .map_err(|e| format!("Failed to parse info JSON: {}", e))?;
+    
+    let service = json
+        .formulae
+        .into_iter()
+        .next()
+        .and_then(|f| f.service)
+        .ok_or_else(|| format!("{} does not declare a service", name))?;
+    
+    let prefix = get_brew_prefix()?;
+    let home = std::env::var("HOME").unwrap_or_default();
+    let mut paths: Vec<PathBuf> = Vec::new();
+    for path in [service.log_path, service.error_log_path]
+        .into_iter()
+        .flatten()
+    {
+        let path = path.replace("$HOMEBREW_PREFIX", &prefix);
+        let path = match path.strip_prefix("~/") {
+            Some(rest) => Path::new(&home).join(rest),
+            None => PathBuf::from(path),
+        };
+        if !paths.contains(&path) {
+            paths.push(path);
+        }
+    }
+    
+    if paths.is_empty() {
+        return Err(format!("{} does not declare log paths", name));
+    }
+    
+    Ok(paths)
+}
+
+/// 文件标识，用于检测日志轮转（文件被替换）
+#[cfg(unix)]
+fn file_identity(meta: &std::fs::Metadata) -> u64 {
+    use std::os::unix::fs::MetadataExt;
+    meta.ino()
+}
+
+#[cfg(not(unix))]
+fn file_identity(_meta: &std::fs::Metadata) -> u64 {
+    0
+}
+
+/// 持续跟踪单个日志文件，处理截断和轮转，按行发送事件
+fn follow_log(app: AppHandle, service: String, path: PathBuf, stop: Arc<AtomicBool>) {
+    let emit_line = |line: &str| {
+        let _ = app.emit(
+            "service-log",
+            serde_json::json!({
+                "service": service,
+                "path": path.to_string_lossy(),
+                "line": line
+            }),
+        );
+    };
+    
+    let mut offset = 0u64;
+    let mut identity = None;
+    let mut pending = String::new();
+    
+    // 先输出最近的若干行
+    if let Ok(content) = std::fs::read(&path) {
+        let text = String::from_utf8_lossy(&content);
+        let lines: Vec<&str> = text.lines().collect();
+        for line in &lines[lines.len().saturating_sub(LOG_BACKLOG_LINES)..] {
+            emit_line(line);
+        }
+        offset = content.len() as u64;
+        identity = std::fs::metadata(&path).ok().map(|m| file_identity(&m));
+    }
+    
+    while !stop.load(Ordering::Relaxed) {
+        std::thread::sleep(LOG_POLL_INTERVAL);
+        
+        let Ok(meta) = std::fs::metadata(&path) else {
+            continue;
+        };
+        let current = file_identity(&meta);
+        if identity != Some(current) || meta.len() < offset {
+            // 文件被轮转或截断，从头开始读
+            identity = Some(current);
+            offset = 0;
+            pending.clear();
+        }
+        if meta.len() == offset {
+            continue;
+        }
+        
+        let Ok(mut file) = File::open(&path) else {
+            continue;
+        };
+        let mut buf = Vec::new();
+        if file.seek(SeekFrom::Start(offset)).is_err() || file.read_to_end(&mut buf).is_err() {
+            continue;
+        }
+        offset += buf.len() as u64;
+        
+        pending.push_str(&String::from_utf8_lossy(&buf));
+        while let Some(pos) = pending.find('\n') {
+            let line: String = pending.drain(..=pos).collect();
+            emit_line(line.trim_end_matches(['\n', '\r']));
+        }
+    }
+}
+
+/// 开始跟踪服务日志，新行通过 `service-log` 事件发送
+#[tauri::command]
+pub async fn tail_service_log(
+    name: String,
+    app: AppHandle,
+    tails: State<'_, ServiceLogTails>,
+) -> Result<Vec<String>, String> {
+    let paths = resolve_service_log_paths(&name)?;
+    
+    let stop = Arc::new(AtomicBool::new(false));
+    {
+        let mut tails = tails.0.lock().map_err(|e| e.to_string())?;
+        if let Some(previous) = tails.insert(name.clone(), stop.clone()) {
+            previous.store(true, Ordering::Relaxed);
+        }
+    }
+    
+    for path in &paths {
+        let (app, service, path, stop) = (app.clone(), name.clone(), path.clone(), stop.clone());
+        std::thread::spawn(move || follow_log(app, service, path, stop));
+    }
+    
+    Ok(paths
+        .iter()
+        .map(|p| p.to_string_lossy().to_string())
+        .collect())
+}
+
+/// 停止跟踪服务日志
+#[tauri::command]
+pub async fn stop_tail_service_log(
+    name: String,
+    tails: State<'_, ServiceLogTails>,
+) -> Result<(), String> {
+    let mut tails = tails.0.lock().map_err(|e| e.to_string())?;
+    if let Some(stop) = tails.remove(&name) {
+        stop.store(true, Ordering::Relaxed);
+    }
+    Ok(())
+}
+
+/// 比较前后两次服务状态，返回状态变化事件
+fn diff_service_states(
+    previous: &HashMap<String, ServiceInfo>,
+    current: &[ServiceInfo],
+) -> Vec<serde_json::Value> {
+    let mut events = Vec::new();
+    
+    for service in current {
+        let old_status = previous.get(&service.name).map(|s| s.status.as_str());
+        if old_status == Some(service.status.as_str()) {
+            continue;
+        }
+        let crashed = service.status == "error"
+            || (service.status != "started" && service.exit_code.is_some_and(|c| c != 0));
+        let change = if crashed {
+            "crashed"
+        } else if service.status == "started" {
+            "started"
+        } else if old_status == Some("started") {
+            "stopped"
+        } else {
+            continue;
+        };
+        events.push(serde_json::json!({
+            "service": service.name,
+            "change": change,
+            "previousStatus": old_status,
+            "status": service.status,
+            "exitCode": service.exit_code
+        }));
+    }
+    
+    events
+}
+
+/// 启动服务状态轮询，状态变化时发送 `service-status` 事件
+#[tauri::command]
+pub async fn start_service_monitor(
+    interval_secs: Option<u64>,
+    app: AppHandle,
+    monitor: State<'_, ServiceMonitor>,
+) -> Result<(), String> {
+    let interval = Duration::from_secs(
+        interval_secs
+            .unwrap_or(DEFAULT_MONITOR_INTERVAL_SECS)
+            .max(1),
+    );
+    let stop = Arc::new(AtomicBool::new(false));
+    {
+        let mut current = monitor.0.lock().map_err(|e| e.to_string())?;
+        if let Some(previous) = current.replace(stop.clone()) {
+            previous.store(true, Ordering::Relaxed);
+        }
+    }
+    
+    std::thread::spawn(move || {
+        let mut known: Option<HashMap<String, ServiceInfo>> = None;
+        while !stop.load(Ordering::Relaxed) {
+            if let Ok(services) = list_services_internal() {
+                if let Some(previous) = &known {
+                    for event in diff_service_states(previous, &services) {
+                        let _ = app.emit("service-status", event);
+                    }
+                }
+                known = Some(services.into_iter().map(|s| (s.name.clone(), s)).collect());
+            }
+            std::thread::sleep(interval);
+        }
+    });
+    
+    Ok(())
+}
+
+/// 停止服务状态轮询
+#[tauri::command]
+pub async fn stop_service_monitor(monitor: State<'_, ServiceMonitor>) -> Result<(), String> {
+    if let Some(stop) = monitor.0.lock().map_err(|e| e.to_string())?.take() {
+        stop.store(true, Ordering::Relaxed);
+    }
+    Ok(())
+}
@@ -0,0 +1,339 @@
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::path::PathBuf;
+use tauri::AppHandle;
+
+use crate::brewfile::{installed_state, installed_taps};
+use crate::history;
+use crate::services::list_services_internal;
+use crate::storage;
+
+/// 快照文件格式版本，结构变化时递增
+const SNAPSHOT_SCHEMA_VERSION: u32 = 1;
+const SNAPSHOT_DIR: &str = "snapshots";
+/// 自动快照的文件名后缀：`<id>.auto.json`，手动快照为 `<id>.json`
+const AUTO_SNAPSHOT_SUFFIX: &str = ".auto.json";
+/// 最多保留的自动快照数量，手动快照不受影响
+const AUTO_SNAPSHOT_KEEP: usize = 50;
+/// 会修改安装状态、需要事先拍快照的 brew 子命令
+const MUTATING_SUBCOMMANDS: [&str; 12] = [
+    "install",
+    "uninstall",
+    "reinstall",
+    "upgrade",
+    "link",
+    "unlink",
+    "pin",
+    "unpin",
+    "tap",
+    "untap",
+    "cleanup",
+    "services",
+];
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct SnapshotFormula {
+    pub name: String,
+    pub full_name: String,
+    /// Cellar 中的所有已安装版本
+    pub versions: Vec<String>,
+    pub linked_version: Option<String>,
+    pub pinned: bool,
+    pub installed_on_request: bool,
+    /// 直接依赖；旧快照中没有该字段
+    #[serde(default)]
+    pub dependencies: Vec<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct SnapshotCask {
+    pub name: String,
+    pub full_name: String,
+    pub version: String,
+}
+
+/// 某一时刻完整的安装状态
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Snapshot {
+    pub schema_version: u32,
+    pub id: String,
+    pub label: String,
+    /// Unix 毫秒
+    pub created_at: u64,
+    /// 是否为操作前自动拍摄
+    pub automatic: bool,
+    pub taps: Vec<String>,
+    pub formulae: Vec<SnapshotFormula>,
+    pub casks: Vec<SnapshotCask>,
+    /// 正在运行的服务
+    pub services: Vec<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct SnapshotSummary {
+    pub id: String,
+    pub label: String,
+    pub created_at: u64,
+    pub automatic: bool,
+    pub formula_count: usize,
+    pub cask_count: usize,
+}
+
+/// 两个快照之间单个包的变化
+#[derive(Debug, Serialize, Deserialize)]
+pub struct PackageChange {
+    pub name: String,
+    #[serde(rename = "type")]
+    pub pkg_type: String,
+    pub versions_before: Vec<String>,
+    pub versions_after: Vec<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct SnapshotDiff {
+    pub from: String,
+    pub to: String,
+    pub added: Vec<PackageChange>,
+    pub removed: Vec<PackageChange>,
+    pub version_changed: Vec<PackageChange>,
+    pub taps_added: Vec<String>,
+    pub taps_removed: Vec<String>,
+}
+
+/// 读取当前安装状态
+pub(crate) fn capture_state(label: &str, automatic: bool) -> Result<Snapshot, String> {
+    let state = installed_state()?;
+    let taps = installed_taps()?;
+    let services = list_services_internal()
+        .unwrap_or_default()
+        .into_iter()
+        .filter(|s| s.status == "started")
+        .map(|s| s.name)
+        .collect();
+    
+    let formulae = state
+        .formulae
+        .into_iter()
+        .map(|f| SnapshotFormula {
+            installed_on_request: f.installed.iter().any(|v| v.installed_on_request),
+            versions: f.installed.into_iter().map(|v| v.version).collect(),
+            linked_version: f.linked_keg,
+            pinned: f.pinned,
+            dependencies: f.dependencies,
+            name: f.name,
+            full_name: f.full_name,
+        })
+        .collect();
+    let casks = state
+        .casks
+        .into_iter()
+        .map(|c| SnapshotCask {
+            name: c.token,
+            full_name: c.full_token,
+            version: c.installed.unwrap_or_default(),
+        })
+        .collect();
+    
+    Ok(Snapshot {
+        schema_version: SNAPSHOT_SCHEMA_VERSION,
+        id: history::new_operation_id(),
+        label: label.to_string(),
+        created_at: history::now_millis(),
+        automatic,
+        taps,
+        formulae,
+        casks,
+        services,
+    })
+}
+
+fn snapshot_dir(app: &AppHandle) -> Result<PathBuf, String> {
+    let dir = storage::data_dir(app)?.join(SNAPSHOT_DIR);
+    std::fs::create_dir_all(&dir).map_err(|e| format!("Failed to create snapshot dir: {}", e))?;
+    Ok(dir)
+}
+
+fn snapshot_file_name(id: &str, automatic: bool) -> String {
+    if automatic {
+        format!("{}{}", id, AUTO_SNAPSHOT_SUFFIX)
+    } else {
+        format!("{}.json", id)
+    }
+}
+
+fn save_snapshot(app: &AppHandle, snapshot: &Snapshot) -> Result<(), String> {
+    let path = snapshot_dir(app)?.join(snapshot_file_name(&snapshot.id, snapshot.automatic));
+    let content = serde_json::to_string_pretty(snapshot)
+        .map_err(|e| format!("Failed to serialize snapshot: {}", e))?;
+    std::fs::write(path, content).map_err(|e| format!("Failed to write snapshot: {}", e))
+}
+
+/// 读取已保存的快照；`current` 表示当前的实时状态
+pub(crate) fn load_snapshot(app: &AppHandle, id: &str) -> Result<Snapshot, String> {
+    if id == "current" {
+        return capture_state("current", false);
+    }
+    // 快照 ID 只包含数字和连字符，防止路径穿越
+    if id.is_empty() || !id.chars().all(|c| c.is_ascii_digit() || c == '-') {
+        return Err(format!("Invalid snapshot id: {}", id));
+    }
+    
+    let dir = snapshot_dir(app)?;
+    let path = [false, true]
+        .into_iter()
+        .map(|automatic| dir.join(snapshot_file_name(id, automatic)))
+        .find(|p| p.exists())
+        .ok_or_else(|| format!("Snapshot not found: {}", id))?;
+    let content = std::fs::read_to_string(&path)
+        .map_err(|e| format!("Failed to read snapshot {}: {}", id, e))?;
+    serde_json::from_str(&content).map_err(|e| format!("Failed to parse snapshot {}: {}", id, e))
+}
+
+/// brew 命令是否会修改安装状态、需要事先拍快照
+pub(crate) fn is_mutating(args: &[String]) -> bool {
+    args.first()
+        .is_some_and(|sub| MUTATING_SUBCOMMANDS.contains(&sub.as_str()))
+}
+
+/// 在一次用户操作开始前自动拍快照；调用方已进入操作队列，多步操作只在开始时调用一次
+pub(crate) fn auto_snapshot(app: &AppHandle, action: &str, package: &str) {
+    if let Ok(snapshot) = capture_state(&format!("Before {} {}", action, package), true) {
+        if save_snapshot(app, &snapshot).is_ok() {
+            let _ = prune_auto_snapshots(app);
+        }
+    }
+}
+
+/// 只保留最新的 AUTO_SNAPSHOT_KEEP 个自动快照；按文件名判断，无需读取快照内容
+fn prune_auto_snapshots(app: &AppHandle) -> Result<(), String> {
+    let dir = snapshot_dir(app)?;
+    let entries =
+        std::fs::read_dir(&dir).map_err(|e| format!("Failed to read snapshot dir: {}", e))?;
+    
+    // ID 形如 `<创建时间毫秒>-<序号>`
+    let mut automatic: Vec<((u64, u64), String)> = entries
+        .filter_map(|e| e.ok())
+        .filter_map(|e| {
+            let file_name = e.file_name().to_string_lossy().to_string();
+            let id = file_name.strip_suffix(AUTO_SNAPSHOT_SUFFIX)?;
+            let (millis, counter) = id.split_once('-')?;
+            let key = (millis.parse().ok()?, counter.parse().ok()?);
+            Some((key, file_name))
+        })
+        .collect();
+    automatic.sort_by_key(|(key, _)| std::cmp::Reverse(*key));
+    
+    for (_, file_name) in automatic.into_iter().skip(AUTO_SNAPSHOT_KEEP) {
+        std::fs::remove_file(dir.join(&file_name))
+            .map_err(|e| format!("Failed to remove snapshot {}: {}", file_name, e))?;
+    }
+    Ok(())
+}
+
+/// 记录当前完整的安装状态
+#[tauri::command]
+pub async fn create_snapshot(label: String, app: AppHandle) -> Result<SnapshotSummary, String> {
+    let snapshot = capture_state(&label, false)?;
+    save_snapshot(&app, &snapshot)?;
+    
+    Ok(SnapshotSummary {
+        formula_count: snapshot.formulae.len(),
+        cask_count: snapshot.casks.len(),
+        id: snapshot.id,
+        label: snapshot.label,
+        created_at: snapshot.created_at,
+        automatic: snapshot.automatic,
+    })
+}
+
+/// 列出所有快照（最新的在前）
+#[tauri::command]
+pub async fn list_snapshots(app: AppHandle) -> Result<Vec<SnapshotSummary>, String> {
+    let entries = std::fs::read_dir(snapshot_dir(&app)?)
+        .map_err(|e| format!("Failed to read snapshot dir: {}", e))?;
+    
+    let mut summaries: Vec<SnapshotSummary> = entries
+        .filter_map(|e| e.ok())
+        .filter_map(|e| std::fs::read_to_string(e.path()).ok())
+        .filter_map(|content| serde_json::from_str::<Snapshot>(&content).ok())
+        .map(|s| SnapshotSummary {
+            formula_count: s.formulae.len(),
+            cask_count: s.casks.len(),
+            id: s.id,
+            label: s.label,
+            created_at: s.created_at,
+            automatic: s.automatic,
+        })
+        .collect();
+    summaries.sort_by_key(|s| std::cmp::Reverse(s.created_at));
+    
+    Ok(summaries)
+}
+
+fn package_versions(snapshot: &Snapshot) -> BTreeMap<(String, &'static str), Vec<String>> {
+    let mut packages = BTreeMap::new();
+    for f in &snapshot.formulae {
+        packages.insert((f.name.clone(), "formula"), f.versions.clone());
+    }
+    for c in &snapshot.casks {
+        packages.insert((c.name.clone(), "cask"), vec![c.version.clone()]);
+    }
+    packages
+}
+
+/// 比较两个快照
+pub(crate) fn diff(from: &Snapshot, to: &Snapshot) -> SnapshotDiff {
+    let before = package_versions(from);
+    let after = package_versions(to);
+    let change = |(name, pkg_type): &(String, &str), b: &[String], a: &[String]| PackageChange {
+        name: name.clone(),
+        pkg_type: pkg_type.to_string(),
+        versions_before: b.to_vec(),
+        versions_after: a.to_vec(),
+    };
+    
+    let mut added = Vec::new();
+    let mut removed = Vec::new();
+    let mut version_changed = Vec::new();
+    
+    for (key, versions) in &after {
+        match before.get(key) {
+            None => added.push(change(key, &[], versions)),
+            Some(old) if old != versions => version_changed.push(change(key, old, versions)),
+            Some(_) => {}
+        }
+    }
+    for (key, versions) in &before {
+        if !after.contains_key(key) {
+            removed.push(change(key, versions, &[]));
+        }
+    }
+    
+    SnapshotDiff {
+        from: from.id.clone(),
+        to: to.id.clone(),
+        added,
+        removed,
+        version_changed,
+        taps_added: to
+            .taps
+            .iter()
+            .filter(|t| !from.taps.contains(t))
+            .cloned()
+            .collect(),
+        taps_removed: from
+            .taps
+            .iter()
+            .filter(|t| !to.taps.contains(t))
+            .cloned()
+            .collect(),
+    }
+}
+
+/// 比较两个快照；任一 ID 可以是 `current`，表示当前状态
+#[tauri::command]
+pub async fn diff_snapshots(a: String, b: String, app: AppHandle) -> Result<SnapshotDiff, String> {
+    let from = load_snapshot(&app, &a)?;
+    let to = load_snapshot(&app, &b)?;
+    Ok(diff(&from, &to))
+}
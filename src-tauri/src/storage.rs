@@ -0,0 +1,34 @@
+use serde::{de::DeserializeOwned, Serialize};
+use std::path::PathBuf;
+use tauri::{AppHandle, Manager};
+
+/// 应用数据目录（不存在时创建）
+pub(crate) fn data_dir(app: &AppHandle) -> Result<PathBuf, String> {
+    let dir = app
+        .path()
+        .app_data_dir()
+        .map_err(|e| format!("Failed to resolve app data dir: {}", e))?;
+    std::fs::create_dir_all(&dir).map_err(|e| format!("Failed to create app data dir: {}", e))?;
+    Ok(dir)
+}
+
+/// 读取数据目录中的 JSON 文件，文件不存在时返回默认值
+pub(crate) fn read_json<T: DeserializeOwned + Default>(app: &AppHandle, file: &str) -> Result<T, String> {
+    let path = data_dir(app)?.join(file);
+    match std::fs::read_to_string(&path) {
+        Ok(content) => serde_json::from_str(&content)
+            .map_err(|e| format!("Failed to parse {}: {}", file, e)),
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(T::default()),
+        Err(e) => Err(format!("Failed to read {}: {}", file, e)),
+    }
+}
+
+/// 写入数据目录中的 JSON 文件（先写临时文件再替换，避免写到一半的文件）
+pub(crate) fn write_json<T: Serialize>(app: &AppHandle, file: &str, value: &T) -> Result<(), String> {
+    let path = data_dir(app)?.join(file);
+    let tmp = path.with_extension("tmp");
+    let content = serde_json::to_string_pretty(value)
+        .map_err(|e| format!("Failed to serialize {}: {}", file, e))?;
+    std::fs::write(&tmp, content).map_err(|e| format!("Failed to write {}: {}", file, e))?;
+    std::fs::rename(&tmp, &path).map_err(|e| format!("Failed to write {}: {}", file, e))
+}
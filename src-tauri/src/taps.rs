@@ -0,0 +1,163 @@
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+use crate::brewfile::installed_state;
+use crate::homebrew::{execute_brew_command, execute_brew_command_with_progress, CommandOutput};
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct TapInfo {
+    pub name: String,
+    pub remote: Option<String>,
+    /// 是否为 Homebrew 官方 tap
+    pub official: bool,
+    pub formula_count: usize,
+    pub cask_count: usize,
+    /// 从该 tap 安装的包数量
+    pub installed_count: usize,
+    pub last_commit: Option<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct TapDetails {
+    #[serde(flatten)]
+    pub info: TapInfo,
+    pub path: String,
+    pub branch: Option<String>,
+    pub formula_names: Vec<String>,
+    pub cask_tokens: Vec<String>,
+    /// 从该 tap 安装的包
+    pub installed_packages: Vec<String>,
+}
+
+#[derive(Debug, Deserialize)]
+struct TapJson {
+    name: String,
+    #[serde(default)]
+    path: String,
+    remote: Option<String>,
+    #[serde(default)]
+    official: bool,
+    #[serde(default)]
+    formula_names: Vec<String>,
+    #[serde(default)]
+    cask_tokens: Vec<String>,
+    last_commit: Option<String>,
+    branch: Option<String>,
+}
+
+impl TapJson {
+    fn into_details(self, installed: &HashMap<String, Vec<String>>) -> TapDetails {
+        let installed_packages = installed.get(&self.name).cloned().unwrap_or_default();
+        TapDetails {
+            info: TapInfo {
+                name: self.name,
+                remote: self.remote,
+                official: self.official,
+                formula_count: self.formula_names.len(),
+                cask_count: self.cask_tokens.len(),
+                installed_count: installed_packages.len(),
+                last_commit: self.last_commit,
+            },
+            path: self.path,
+            branch: self.branch,
+            formula_names: self.formula_names,
+            cask_tokens: self.cask_tokens,
+            installed_packages,
+        }
+    }
+}
+
+/// 按 tap 分组的已安装包
+pub(crate) fn installed_packages_by_tap() -> Result<HashMap<String, Vec<String>>, String> {
+    let state = installed_state()?;
+    
+    let mut by_tap: HashMap<String, Vec<String>> = HashMap::new();
+    for f in state.formulae {
+        if let Some(tap) = f.tap {
+            by_tap.entry(tap).or_default().push(f.name);
+        }
+    }
+    for c in state.casks {
+        if let Some(tap) = c.tap {
+            by_tap.entry(tap).or_default().push(c.token);
+        }
+    }
+    
+    Ok(by_tap)
+}
+
+fn tap_info_json(args: &[&str]) -> Result<Vec<TapJson>, String> {
+    let output = execute_brew_command(args)?;
+    
+    if !output.success {
+        return Err(format!("Failed to get tap info: {}", output.stderr));
+    }
+    
+    serde_json::from_str(&output.stdout).map_err(|e| format!("Failed to parse tap JSON: {}", e))
+}
+
+/// 列出所有已添加的 tap
+#[tauri::command]
+pub async fn list_taps() -> Result<Vec<TapInfo>, String> {
+    let installed = installed_packages_by_tap()?;
+    let taps = tap_info_json(&["tap-info", "--installed", "--json"])?;
+    
+    Ok(taps
+        .into_iter()
+        .map(|t| t.into_details(&installed).info)
+        .collect())
+}
+
+/// 获取单个 tap 的详情
+#[tauri::command]
+pub async fn tap_info(name: String) -> Result<TapDetails, String> {
+    let installed = installed_packages_by_tap()?;
+    let tap = tap_info_json(&["tap-info", "--json", &name])?
+        .into_iter()
+        .next()
+        .ok_or_else(|| format!("Tap not found: {}", name))?;
+    
+    Ok(tap.into_details(&installed))
+}
+
+/// 添加 tap（brew tap），可指定自定义仓库地址
+#[tauri::command]
+pub async fn add_tap(
+    name: String,
+    url: Option<String>,
+    window: tauri::Window,
+) -> Result<CommandOutput, String> {
+    let mut args = vec!["tap".to_string(), name.clone()];
+    if let Some(url) = url.filter(|u| !u.trim().is_empty()) {
+        args.push(url);
+    }
+    
+    execute_brew_command_with_progress(&args, &window, &name, "add_tap").await
+}
+
+/// 移除 tap（brew untap）。若仍有从该 tap 安装的包，除非 force 否则拒绝执行
+#[tauri::command]
+pub async fn remove_tap(
+    name: String,
+    force: bool,
+    window: tauri::Window,
+) -> Result<CommandOutput, String> {
+    if !force {
+        let installed = installed_packages_by_tap()?;
+        if let Some(packages) = installed.get(&name).filter(|p| !p.is_empty()) {
+            return Err(format!(
+                "Tap {} still has installed packages: {}",
+                name,
+                packages.join(", ")
+            ));
+        }
+    }
+    
+    let mut args = vec!["untap".to_string()];
+    if force {
+        args.push("--force".to_string());
+    }
+    args.push(name.clone());
+    
+    execute_brew_command_with_progress(&args, &window, &name, "remove_tap").await
+}
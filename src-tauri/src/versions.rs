@@ -0,0 +1,525 @@
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+use tauri::Manager;
+
+use crate::homebrew::{
+    execute_brew_command, execute_brew_command_queued, get_brew_cache, get_brew_prefix,
+    CommandOutput,
+};
+use crate::links::link_conflicts;
+use crate::operations::{self, QueueGuard};
+use crate::snapshots::auto_snapshot;
+
+/// 某个 formula 的一个可选版本（默认 formula 或 `name@version` 形式的版本化 formula）
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct FormulaVersion {
+    pub name: String,
+    /// 仓库中的最新稳定版本；仅存在于 Cellar 中的旧 formula 为空
+    pub version: String,
+    /// 是否为默认（非版本化）formula
+    pub is_default: bool,
+    pub keg_only: bool,
+    /// Cellar 中已有的 keg 版本
+    pub installed_versions: Vec<String>,
+    /// 当前链接到前缀中的版本
+    pub linked_version: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+struct VersionInfoJson {
+    formulae: Vec<VersionFormulaJson>,
+}
+
+#[derive(Debug, Deserialize)]
+struct VersionFormulaJson {
+    name: String,
+    #[serde(default)]
+    versioned_formulae: Vec<String>,
+    versions: VersionsJson,
+    #[serde(default)]
+    keg_only: bool,
+    linked_keg: Option<String>,
+    #[serde(default)]
+    installed: Vec<InstalledJson>,
+}
+
+#[derive(Debug, Deserialize)]
+struct VersionsJson {
+    stable: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+struct InstalledJson {
+    version: String,
+}
+
+/// 去掉版本后缀，`python@3.11` -> `python`
+pub(crate) fn base_formula_name(name: &str) -> &str {
+    name.split('@').next().unwrap_or(name)
+}
+
+/// 列出 Cellar 中某个 formula 的所有 keg 版本
+pub(crate) fn list_kegs(prefix: &str, name: &str) -> Vec<String> {
+    let mut kegs: Vec<String> = std::fs::read_dir(Path::new(prefix).join("Cellar").join(name))
+        .map(|entries| {
+            entries
+                .filter_map(|e| e.ok())
+                .filter(|e| e.path().is_dir())
+                .map(|e| e.file_name().to_string_lossy().to_string())
+                .collect()
+        })
+        .unwrap_or_default();
+    kegs.sort();
+    kegs
+}
+
+/// 下载缓存中的 bottle 文件
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CachedBottle {
+    pub version: String,
+    pub path: String,
+}
+
+/// 从缓存文件名中解析出 formula 名和版本
+///
+/// 文件名形如 `<sha256>--name--1.2.3_1.arm64_sonoma.bottle.tar.gz`，缓存根目录下的链接没有哈希前缀
+pub(crate) fn parse_bottle_file_name(file_name: &str) -> Option<(String, String)> {
+    let (stem, _) = file_name.split_once(".bottle.")?;
+    if file_name.ends_with(".json") {
+        return None;
+    }
+    let stem = match stem.split_once("--") {
+        Some((hash, rest)) if hash.len() == 64 && hash.chars().all(|c| c.is_ascii_hexdigit()) => {
+            rest
+        }
+        _ => stem,
+    };
+    let (name, versioned) = stem.split_once("--")?;
+    let (version, _tag) = versioned.rsplit_once('.')?;
+    Some((name.to_string(), version.to_string()))
+}
+
+/// 查找下载缓存中某个 formula 的 bottle
+pub(crate) fn find_cached_bottles(name: &str) -> Vec<CachedBottle> {
+    let Ok(cache) = get_brew_cache() else {
+        return vec![];
+    };
+    let cache = PathBuf::from(cache);
+    
+    let mut bottles: Vec<CachedBottle> = Vec::new();
+    for dir in [cache.join("downloads"), cache] {
+        let Ok(entries) = std::fs::read_dir(&dir) else {
+            continue;
+        };
+        for entry in entries.filter_map(|e| e.ok()) {
+            let file_name = entry.file_name().to_string_lossy().to_string();
+            let Some((bottle_name, version)) = parse_bottle_file_name(&file_name) else {
+                continue;
+            };
+            if bottle_name == name && !bottles.iter().any(|b| b.version == version) {
+                bottles.push(CachedBottle {
+                    version,
+                    path: entry.path().to_string_lossy().to_string(),
+                });
+            }
+        }
+    }
+    
+    bottles
+}
+
+/// 把 opt 链接指向 Cellar 中已有的某个 keg（需要在操作队列中执行）
+#[cfg(unix)]
+fn point_opt_link(prefix: &Path, name: &str, version: &str) -> std::io::Result<()> {
+    let opt = prefix.join("opt").join(name);
+    if std::fs::symlink_metadata(&opt).is_ok() {
+        std::fs::remove_file(&opt)?;
+    }
+    let target = Path::new("../Cellar").join(name).join(version);
+    std::os::unix::fs::symlink(target, opt)
+}
+
+#[cfg(not(unix))]
+fn point_opt_link(_prefix: &Path, _name: &str, _version: &str) -> std::io::Result<()> {
+    Err(std::io::Error::new(
+        std::io::ErrorKind::Unsupported,
+        "Switching kegs is only supported on Unix",
+    ))
+}
+
+/// 切换到 Cellar 中已有的某个版本：取消链接、更新 opt 链接，需要时重新链接
+pub(crate) async fn activate_keg(
+    queue: &QueueGuard,
+    name: &str,
+    version: &str,
+    link: bool,
+    window: &tauri::Window,
+    action: &str,
+) -> Result<CommandOutput, String> {
+    let prefix = PathBuf::from(get_brew_prefix()?);
+    if !prefix.join("Cellar").join(name).join(version).is_dir() {
+        return Err(format!("{} {} is not in the Cellar", name, version));
+    }
+    
+    let args = vec!["unlink".to_string(), name.to_string()];
+    let mut result = execute_brew_command_queued(queue, &args, window, name, action).await?;
+    if !result.success {
+        return Ok(result);
+    }
+    
+    point_opt_link(&prefix, name, version)
+        .map_err(|e| format!("Failed to switch {} to {}: {}", name, version, e))?;
+    // opt 链接不经过 brew，需要单独让已安装列表失效
+    crate::search_index::forget_installed(window.app_handle());
+    result
+        .stdout
+        .push_str(&format!("Switched {} to {}\n", name, version));
+    
+    if link {
+        // 不使用 --overwrite：冲突的文件可能属于其他 formula，交给用户在链接预览中确认
+        let args = vec!["link".to_string(), name.to_string()];
+        result.append(execute_brew_command_queued(queue, &args, window, name, action).await?);
+        if !result.success {
+            let conflicts = link_conflicts(name, false).unwrap_or_default();
+            if !conflicts.is_empty() {
+                result.stderr.push_str(&format!(
+                    "{} {} is active but not linked because these files already exist:\n{}\n",
+                    name,
+                    version,
+                    conflicts.join("\n")
+                ));
+            }
+        }
+    }
+    
+    Ok(result)
+}
+
+/// 从缓存的 bottle 安装指定版本：先取消链接当前版本，成功后再强制安装
+pub(crate) async fn install_cached_bottle(
+    queue: &QueueGuard,
+    name: &str,
+    path: &str,
+    window: &tauri::Window,
+    action: &str,
+) -> Result<CommandOutput, String> {
+    if !Path::new(path).is_file() {
+        return Err(format!("Cached bottle no longer exists: {}", path));
+    }
+    
+    let mut result = CommandOutput {
+        success: true,
+        stdout: String::new(),
+        stderr: String::new(),
+        exit_code: 0,
+    };
+    let linked = formula_info(&[name])
+        .map(|info| info.first().is_some_and(|f| f.linked_keg.is_some()))
+        .unwrap_or(false);
+    if linked {
+        let args = vec!["unlink".to_string(), name.to_string()];
+        result.append(execute_brew_command_queued(queue, &args, window, name, action).await?);
+        if !result.success {
+            return Ok(result);
+        }
+    }
+    
+    let args = vec![
+        "install".to_string(),
+        "--force".to_string(),
+        path.to_string(),
+    ];
+    result.append(execute_brew_command_queued(queue, &args, window, name, action).await?);
+    Ok(result)
+}
+
+fn formula_info(names: &[&str]) -> Result<Vec<VersionFormulaJson>, String> {
+    let mut args = vec!["info", "--json=v2"];
+    args.extend_from_slice(names);
+    let output = execute_brew_command(&args)?;
+    
+    if !output.success {
+        return Err(format!("Failed to get package info: {}", output.stderr));
+    }
+    
+    let json: VersionInfoJson = serde_json::from_str(&output.stdout)
+        .map_err(|e| format!("Failed to parse info JSON: {}", e))?;
+    
+    Ok(json.formulae)
+}
+
+/// 列出 formula 的所有可用版本：版本化 formula 以及 Cellar 中已有的 keg
+#[tauri::command]
+pub async fn list_versions(name: String) -> Result<Vec<FormulaVersion>, String> {
+    let prefix = get_brew_prefix()?;
+    
+    // 基础名可能是别名（如 python -> python@3.13），以 brew info 解析出的名称为默认版本
+    let requested = base_formula_name(&name).to_string();
+    let resolved = formula_info(&[&requested])?.into_iter().next();
+    let default_name = resolved
+        .as_ref()
+        .map(|f| f.name.clone())
+        .unwrap_or_else(|| requested.clone());
+    let base = base_formula_name(&default_name).to_string();
+    
+    let mut names = vec![default_name.clone()];
+    if let Some(formula) = resolved {
+        for versioned in formula.versioned_formulae {
+            if !names.contains(&versioned) {
+                names.push(versioned);
+            }
+        }
+    }
+    
+    let name_refs: Vec<&str> = names.iter().map(|n| n.as_str()).collect();
+    let mut versions: Vec<FormulaVersion> = formula_info(&name_refs)?
+        .into_iter()
+        .map(|f| FormulaVersion {
+            is_default: f.name == default_name,
+            version: f.versions.stable.unwrap_or_default(),
+            keg_only: f.keg_only,
+            installed_versions: {
+                let kegs = list_kegs(&prefix, &f.name);
+                if kegs.is_empty() {
+                    f.installed.into_iter().map(|i| i.version).collect()
+                } else {
+                    kegs
+                }
+            },
+            linked_version: f.linked_keg,
+            name: f.name,
+        })
+        .collect();
+    
+    // Cellar 中可能还有已从仓库删除的旧版本化 formula
+    if let Ok(entries) = std::fs::read_dir(Path::new(&prefix).join("Cellar")) {
+        let versioned_prefix = format!("{}@", base);
+        for entry in entries.filter_map(|e| e.ok()) {
+            let dir_name = entry.file_name().to_string_lossy().to_string();
+            if dir_name.starts_with(&versioned_prefix)
+                && !versions.iter().any(|v| v.name == dir_name)
+            {
+                versions.push(FormulaVersion {
+                    installed_versions: list_kegs(&prefix, &dir_name),
+                    name: dir_name,
+                    version: String::new(),
+                    is_default: false,
+                    keg_only: true,
+                    linked_version: None,
+                });
+            }
+        }
+    }
+    
+    Ok(versions)
+}
+
+/// 安装并切换到指定的版本化 formula，可选地取消链接当前已链接的其他版本
+#[tauri::command]
+pub async fn switch_version(
+    name: String,
+    unlink_default: bool,
+    window: tauri::Window,
+) -> Result<CommandOutput, String> {
+    let queue = operations::enter_queue();
+    auto_snapshot(window.app_handle(), "switch_version", &name);
+    switch_to_versioned(&queue, &name, unlink_default, &window, "switch_version").await
+}
+
+/// 同一 formula 的各个版本（默认、版本化以及 Cellar 中的旧版本）中当前已链接的
+fn linked_versions(base: &str) -> Result<Vec<String>, String> {
+    let prefix = get_brew_prefix()?;
+    let mut names = vec![base.to_string()];
+    if let Some(formula) = formula_info(&[base])?.into_iter().next() {
+        names.push(formula.name);
+        names.extend(formula.versioned_formulae);
+    }
+    names.sort();
+    names.dedup();
+    let refs: Vec<&str> = names.iter().map(|n| n.as_str()).collect();
+    let mut formulae = formula_info(&refs)?;
+    
+    // 已从仓库删除的旧版本化 formula 逐个查询，查不到的忽略
+    if let Ok(entries) = std::fs::read_dir(Path::new(&prefix).join("Cellar")) {
+        let versioned_prefix = format!("{}@", base);
+        for entry in entries.filter_map(|e| e.ok()) {
+            let dir_name = entry.file_name().to_string_lossy().to_string();
+            if dir_name.starts_with(&versioned_prefix) && !names.contains(&dir_name) {
+                formulae.extend(formula_info(&[&dir_name]).unwrap_or_default());
+            }
+        }
+    }
+    
+    Ok(formulae
+        .into_iter()
+        .filter(|f| f.linked_keg.is_some())
+        .map(|f| f.name)
+        .collect())
+}
+
+/// 安装（如未安装）并链接版本化 formula，可选地先取消链接当前已链接的其他版本
+async fn switch_to_versioned(
+    queue: &QueueGuard,
+    name: &str,
+    unlink_current: bool,
+    window: &tauri::Window,
+    action: &str,
+) -> Result<CommandOutput, String> {
+    let base = base_formula_name(name).to_string();
+    let target = formula_info(&[name])?
+        .into_iter()
+        .next()
+        .ok_or_else(|| "Package not found".to_string())?;
+    
+    let mut result = CommandOutput {
+        success: true,
+        stdout: String::new(),
+        stderr: String::new(),
+        exit_code: 0,
+    };
+    
+    if target.installed.is_empty() {
+        let args = vec!["install".to_string(), name.to_string()];
+        result.append(execute_brew_command_queued(queue, &args, window, name, action).await?);
+        if !result.success {
+            return Ok(result);
+        }
+    }
+    
+    if unlink_current {
+        for linked in linked_versions(&base)?.into_iter().filter(|n| n != name) {
+            let args = vec!["unlink".to_string(), linked];
+            result.append(execute_brew_command_queued(queue, &args, window, name, action).await?);
+            if !result.success {
+                return Ok(result);
+            }
+        }
+    }
+    
+    // keg-only 的版本化 formula 需要 --force 才能链接
+    let mut args = vec!["link".to_string()];
+    if target.keg_only {
+        args.push("--force".to_string());
+    }
+    args.push(name.to_string());
+    result.append(execute_brew_command_queued(queue, &args, window, name, action).await?);
+    
+    Ok(result)
+}
+
+/// 可以切换回去的旧版本
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RestorableVersion {
+    pub version: String,
+    /// keg（Cellar 中的旧 keg）/ cache（缓存的 bottle）/ versioned_formula（`name@version`）
+    pub source: String,
+    /// 要安装或链接的 formula 名
+    pub formula: String,
+    pub path: Option<String>,
+    /// 当前正在使用的版本
+    pub current: bool,
+}
+
+/// 收集一个 formula 可恢复的版本
+fn restorable_versions(name: &str) -> Result<Vec<RestorableVersion>, String> {
+    let prefix = get_brew_prefix()?;
+    let info = formula_info(&[name])?
+        .into_iter()
+        .next()
+        .ok_or_else(|| "Package not found".to_string())?;
+    let current = info
+        .linked_keg
+        .clone()
+        .or_else(|| info.installed.last().map(|v| v.version.clone()));
+    
+    let mut versions: Vec<RestorableVersion> = list_kegs(&prefix, name)
+        .into_iter()
+        .map(|version| RestorableVersion {
+            current: current.as_deref() == Some(version.as_str()),
+            path: Some(
+                Path::new(&prefix)
+                    .join("Cellar")
+                    .join(name)
+                    .join(&version)
+                    .to_string_lossy()
+                    .to_string(),
+            ),
+            formula: name.to_string(),
+            source: "keg".to_string(),
+            version,
+        })
+        .collect();
+    
+    for bottle in find_cached_bottles(name) {
+        if !versions.iter().any(|v| v.version == bottle.version) {
+            versions.push(RestorableVersion {
+                version: bottle.version,
+                source: "cache".to_string(),
+                formula: name.to_string(),
+                path: Some(bottle.path),
+                current: false,
+            });
+        }
+    }
+    
+    let versioned: Vec<&str> = info.versioned_formulae.iter().map(|v| v.as_str()).collect();
+    if !versioned.is_empty() {
+        for formula in formula_info(&versioned)? {
+            versions.push(RestorableVersion {
+                version: formula.versions.stable.unwrap_or_default(),
+                source: "versioned_formula".to_string(),
+                formula: formula.name,
+                path: None,
+                current: false,
+            });
+        }
+    }
+    
+    Ok(versions)
+}
+
+/// 列出可以降级到的版本：Cellar 中的旧 keg、缓存的 bottle 和版本化 formula
+#[tauri::command]
+pub async fn list_restorable_versions(name: String) -> Result<Vec<RestorableVersion>, String> {
+    restorable_versions(&name)
+}
+
+/// 把 formula 降级到指定版本，可选地在之后锁定版本
+///
+/// `version` 可以是版本号，也可以是版本化 formula 名（如 `python@3.11`）
+#[tauri::command]
+pub async fn downgrade_package(
+    name: String,
+    version: String,
+    pin: bool,
+    window: tauri::Window,
+) -> Result<CommandOutput, String> {
+    const ACTION: &str = "downgrade_package";
+    let target = restorable_versions(&name)?
+        .into_iter()
+        .find(|v| v.version == version || v.formula == version)
+        .ok_or_else(|| format!("{} {} is not available locally", name, version))?;
+    
+    let queue = operations::enter_queue();
+    auto_snapshot(window.app_handle(), ACTION, &name);
+    let mut result = match target.source.as_str() {
+        "keg" => {
+            let linked = formula_info(&[&name])?
+                .first()
+                .is_some_and(|f| f.linked_keg.is_some());
+            activate_keg(&queue, &name, &target.version, linked, &window, ACTION).await?
+        }
+        "cache" => {
+            let path = target.path.clone().unwrap_or_default();
+            install_cached_bottle(&queue, &name, &path, &window, ACTION).await?
+        }
+        _ => switch_to_versioned(&queue, &target.formula, true, &window, ACTION).await?,
+    };
+    
+    if result.success && pin {
+        let args = vec!["pin".to_string(), target.formula.clone()];
+        result.append(execute_brew_command_queued(&queue, &args, &window, &name, ACTION).await?);
+    }
+    
+    Ok(result)
+}
@@ -117,10 +117,9 @@ function App() {
     setIsSearching(true);
     try {
       const results = await searchPackages(query);
-      const installedNames = new Set(packages.map(p => p.name));
-      setSearchResults(results.map(pkg => ({ ...pkg, installed: installedNames.has(pkg.name) })));
+      setSearchResults(results);
     } finally { setIsSearching(false); }
-  }, [searchPackages, packages]);
+  }, [searchPackages]);
 
   const displayPackages = useMemo(() => {
     const source = searchQuery ? searchResults : packages;
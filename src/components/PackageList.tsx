@@ -1,4 +1,4 @@
-import type { Package } from '../types';
+import type { Package, SearchResult } from '../types';
 import type { Language } from '../types/preferences';
 import { t } from '../i18n';
 import './PackageList.css';
@@ -37,6 +37,8 @@ export function PackageList({ packages, pinnedPackages, favoritePackages, select
       {packages.map((pkg) => {
         const isPinned = pinnedPackages.includes(pkg.name);
         const isFavorite = favoritePackages.includes(pkg.name);
+        const latestVersion = (pkg as SearchResult).latestVersion;
+        const newerVersion = pkg.installed && latestVersion !== pkg.version ? latestVersion : undefined;
         return (
           <div
             key={`${pkg.name}-${pkg.type}`}
@@ -59,7 +61,9 @@ export function PackageList({ packages, pinnedPackages, favoritePackages, select
             )}
             <div className="package-item__meta">
               {pkg.version && (
-                <span className="package-item__version">{pkg.version}</span>
+                <span className="package-item__version">
+                  {newerVersion ? `${pkg.version} → ${newerVersion}` : pkg.version}
+                </span>
               )}
               {pkg.outdated && (
                 <span className="package-item__badge package-item__badge--outdated">
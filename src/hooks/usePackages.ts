@@ -1,7 +1,7 @@
 import { useState, useCallback, useEffect } from 'react';
 import { invoke } from '@tauri-apps/api/core';
 import { listen, UnlistenFn } from '@tauri-apps/api/event';
-import type { Package, PackageInfo, OutdatedPackage, CommandOutput, HomebrewInfo, DependencyInfo } from '../types';
+import type { Package, PackageInfo, OutdatedPackage, CommandOutput, HomebrewInfo, DependencyInfo, SearchResult } from '../types';
 
 interface ProgressEvent {
   package: string;
@@ -20,7 +20,7 @@ interface UsePackagesReturn {
   homebrewInfo: HomebrewInfo | null;
   refresh: () => Promise<void>;
   selectPackage: (pkg: Package | null) => void;
-  searchPackages: (query: string) => Promise<Package[]>;
+  searchPackages: (query: string) => Promise<SearchResult[]>;
   installPackage: (name: string, isCask: boolean, onProgress: (line: string) => void) => Promise<CommandOutput>;
   uninstallPackage: (name: string, isCask: boolean, onProgress: (line: string) => void) => Promise<CommandOutput>;
   upgradePackage: (name: string, isCask: boolean, onProgress: (line: string) => void) => Promise<CommandOutput>;
@@ -88,9 +88,9 @@ export function usePackages(): UsePackagesReturn {
     }
   }, []);
 
-  const searchPackages = useCallback(async (query: string): Promise<Package[]> => {
+  const searchPackages = useCallback(async (query: string): Promise<SearchResult[]> => {
     try {
-      return await invoke<Package[]>('search_packages', { query });
+      return await invoke<SearchResult[]>('search_packages', { query });
     } catch (e) {
       console.error('Search failed:', e);
       return [];
@@ -19,6 +19,36 @@ export interface Package {
   outdated: boolean;
   /** 包描述 */
   description?: string;
+  /** keg 是否已链接到前缀（仅 formula） */
+  linked?: boolean;
+  /** 是否声明了后台服务（brew services） */
+  hasService?: boolean;
+}
+
+/**
+ * 搜索命中的字段，用于高亮
+ */
+export interface SearchMatch {
+  /** 命中的字段：name、alias 或 description */
+  field: 'name' | 'alias' | 'description';
+  /** 字段原文 */
+  value: string;
+  /** 命中方式：exact、prefix、token、substring、fuzzy、word、partial */
+  kind: string;
+  /** 命中的字符区间 [start, end) */
+  ranges: [number, number][];
+}
+
+/**
+ * 带评分的搜索结果
+ */
+export interface SearchResult extends Package {
+  /** 索引中的最新版本；已安装时 version 为本地版本 */
+  latestVersion?: string;
+  /** 相关度得分，越高越靠前 */
+  score: number;
+  /** 命中的字段 */
+  matches: SearchMatch[];
 }
 
 /**